@@ -0,0 +1,759 @@
+use async_trait::async_trait;
+use snafu::{OptionExt, ResultExt};
+use std::collections::{BTreeMap, HashMap};
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::sync::{oneshot, watch};
+use tradebot_protos::messages::enums::MessageType;
+use tradebot_protos::messages::{
+    AppResponse, ChartRequest, OverallFromLogRequest, OverallRequest, ReadFromDirRequest,
+    ReadFromDirResponse, RunYaml, StrategyFromLogRequest,
+};
+use zenoh_node::builder::NodeBuilder;
+use zenoh_node::error::NodeError;
+use zenoh_node::node::{Node, Subscribe, Subscriber, SubscriberError};
+
+use crate::config::ServiceConfig;
+use crate::error::{
+    self, CommandError, InvalidArgumentSnafu, NoReplaySnafu, RecordingSnafu, ReplaySnafu,
+    UnknownFeedSnafu,
+};
+use crate::feeds::{
+    self, AppFeeds, CloseReason, ConnectSnafu, Feed, FeedClosed, FeedError, FeedInfo,
+};
+use crate::forwarder::{EventForwarder, EventMetrics};
+use crate::health::{BackendService, HealthMonitor, ServiceHealth};
+use crate::identity;
+use crate::recorder::{Recorder, RecordingStatus};
+use crate::replay::{ReplayPlayer, ReplayStatus};
+use crate::requests::{OutstandingRequests, RequestKind};
+use crate::session::SessionStore;
+use crate::sink::EventSink;
+use crate::subscriptions::{
+    AdvertisedTopic, AppSubscriber, SubscriptionRegistry, TopicInventory, TopicStatus,
+};
+use crate::supervisor::{ConnectionStatus, ServiceLink};
+use crate::windows::{Target, WindowRouter, WindowScope};
+
+// Everything behind the app, shared by all of its windows. Methods are what the frontend can ask
+// for, app feed messages and everything else that happens reach it through the event sink
+pub struct AppCore {
+    pub(crate) sink: Arc<dyn EventSink>,
+    pub(crate) service_link: Arc<ServiceLink>,
+    pub(crate) health_monitor: Arc<HealthMonitor>,
+    pub(crate) outstanding_requests: Arc<OutstandingRequests>,
+    pub(crate) registry: Arc<SubscriptionRegistry>,
+    pub(crate) topic_inventory: Arc<TopicInventory>,
+    pub(crate) router: Arc<WindowRouter>,
+    pub(crate) session: Arc<SessionStore>,
+    pub(crate) recorder: Arc<Recorder>,
+    pub(crate) replay_player: Arc<ReplayPlayer>,
+    pub(crate) feeds: Arc<AppFeeds>,
+    pub(crate) forwarder: Arc<EventForwarder>,
+    // Bumped after every app response was handled, the feeds it advertised are followed by then
+    pub(crate) app_responses: watch::Sender<u64>,
+}
+
+impl AppCore {
+    pub fn new(
+        service_config: ServiceConfig,
+        client_id: String,
+        recording_dir: Option<PathBuf>,
+        sink: Arc<dyn EventSink>,
+    ) -> Arc<Self> {
+        // App feed messages go through the forwarder so fast streams are batched per frame
+        let forward_sink = sink.clone();
+        let forwarder = EventForwarder::new(
+            service_config.frame_interval(),
+            move |event, target, payload| forward_sink.emit(event, target, payload),
+        );
+
+        Arc::new(Self {
+            sink,
+            health_monitor: Arc::new(HealthMonitor::new(service_config.heartbeat_interval())),
+            outstanding_requests: Arc::new(OutstandingRequests::new(
+                service_config.request_timeout(),
+            )),
+            registry: Arc::new(SubscriptionRegistry::app()),
+            topic_inventory: Arc::new(TopicInventory::new()),
+            router: Arc::new(WindowRouter::new()),
+            session: Arc::new(SessionStore::new()),
+            recorder: Arc::new(Recorder::new(recording_dir)),
+            replay_player: Arc::new(ReplayPlayer::new()),
+            feeds: Arc::new(AppFeeds::new()),
+            forwarder: Arc::new(forwarder),
+            app_responses: watch::Sender::new(0),
+            service_link: Arc::new(ServiceLink::new(service_config, client_id)),
+        })
+    }
+
+    // Run for as long as the app does. Connects to services and subscribes to service responses,
+    // reconnecting whenever the connection fails, keeps an eye on service health, flushes
+    // forwarded events every frame and plays back recorded sessions
+    pub async fn run(self: Arc<Self>) {
+        let core = self.clone();
+        tokio::join!(
+            self.forwarder.clone().run(),
+            self.replay_player.clone().run(self.clone()),
+            self.health_monitor
+                .clone()
+                .run(self.service_link.clone(), self.sink.clone()),
+            self.service_link
+                .clone()
+                .supervise(self.sink.clone(), move || {
+                    core.health_monitor.reconnecting();
+                    AppServiceSubscriber::new(core.clone())
+                }),
+        );
+    }
+
+    pub fn router(&self) -> &WindowRouter {
+        &self.router
+    }
+
+    // Wait until there is a live connection to services
+    pub async fn connected(&self) {
+        self.service_link.connected().await;
+    }
+
+    pub fn connection_status(&self) -> ConnectionStatus {
+        self.service_link.status()
+    }
+
+    pub fn service_health(&self) -> Vec<ServiceHealth> {
+        self.health_monitor.snapshot()
+    }
+
+    pub fn topic_inventory(&self) -> Vec<AdvertisedTopic> {
+        self.topic_inventory.snapshot()
+    }
+
+    pub fn forwarder_metrics(&self) -> HashMap<&'static str, EventMetrics> {
+        self.forwarder.metrics()
+    }
+
+    // Declare what a window shows so only matching strategy streams are sent to it
+    pub fn set_window_scope(&self, label: &str, scope: WindowScope) -> Result<(), CommandError> {
+        error::require("strategy id", &scope.strategy_id)?;
+        self.router.set_scope(label, scope);
+
+        Ok(())
+    }
+
+    // Everything received this session for the batch and, if a strategy id is given, the strategy
+    // chart, keyed by event name so a view can hydrate without asking TradeBot again. While a
+    // recording is loaded, everything replayed from it instead
+    pub fn session_snapshot(
+        &self,
+        batch_id: String,
+        strategy_id: String,
+        symbol: String,
+        period_s: u32,
+    ) -> Result<BTreeMap<&'static str, serde_json::Value>, CommandError> {
+        error::require("batch id", &batch_id)?;
+
+        let scope = (!strategy_id.is_empty()).then(|| WindowScope {
+            batch_id: batch_id.clone(),
+            strategy_id,
+            symbol,
+            period_s,
+        });
+
+        let replayed = self.replay_player.snapshot(&batch_id, scope.as_ref());
+        Ok(replayed.unwrap_or_else(|| self.session.snapshot(&batch_id, scope.as_ref())))
+    }
+
+    // Start writing every app feed message to a new session file in the recording dir
+    pub fn start_recording(&self) -> Result<RecordingStatus, CommandError> {
+        let status = self
+            .recorder
+            .start(&self.feeds.strategy_list())
+            .context(RecordingSnafu)?;
+        self.sink.emit_all("recording", Some(&status));
+
+        Ok(status)
+    }
+
+    // Returns the finished recording, None if nothing was being recorded
+    pub fn stop_recording(&self) -> Result<Option<RecordingStatus>, CommandError> {
+        // Recording is over even if flushing the last records failed
+        let status = self.recorder.stop();
+        self.sink.emit_all("recording", None::<RecordingStatus>);
+
+        status.context(RecordingSnafu)
+    }
+
+    // Finish a recording that is still going when the app exits, records buffered for the session
+    // file would be lost otherwise. Nothing is left to tell about it
+    pub fn shutdown(&self) {
+        if let Err(e) = self.recorder.stop() {
+            eprintln!("Failed to finish recording on exit: {}", e);
+        }
+    }
+
+    pub fn recording_status(&self) -> Option<RecordingStatus> {
+        self.recorder.status()
+    }
+
+    // Load a recorded session for replay, paused at its start
+    pub fn replay_open(&self, path: &Path) -> Result<ReplayStatus, CommandError> {
+        let status = self.replay_player.open(path).context(ReplaySnafu {
+            path: path.display().to_string(),
+        })?;
+        self.sink.emit_all("replay", &status);
+
+        Ok(status)
+    }
+
+    pub fn replay_play(&self) -> Result<ReplayStatus, CommandError> {
+        self.send_replay_status(self.replay_player.play())
+    }
+
+    pub fn replay_pause(&self) -> Result<ReplayStatus, CommandError> {
+        self.send_replay_status(self.replay_player.pause())
+    }
+
+    // Jump to a time from the start of the recording
+    pub fn replay_seek(&self, position_ns: u64) -> Result<ReplayStatus, CommandError> {
+        self.send_replay_status(self.replay_player.seek(position_ns))
+    }
+
+    // Playback speed relative to how the session was recorded, 2 replays twice as fast
+    pub fn replay_speed(&self, speed: f64) -> Result<ReplayStatus, CommandError> {
+        if !(speed.is_finite() && speed > 0.0) {
+            return InvalidArgumentSnafu {
+                name: "speed",
+                reason: "must be greater than 0",
+            }
+            .fail();
+        }
+
+        self.send_replay_status(self.replay_player.speed(speed))
+    }
+
+    // Stop replaying and unload the recording, the frontend goes back to live requests
+    pub fn replay_close(&self) -> Result<ReplayStatus, CommandError> {
+        let status = self.replay_player.close().context(NoReplaySnafu)?;
+        self.sink.emit_all("replay_closed", &status);
+
+        Ok(status)
+    }
+
+    pub fn replay_status(&self) -> Option<ReplayStatus> {
+        self.replay_player.status()
+    }
+
+    fn send_replay_status(
+        &self,
+        status: Option<ReplayStatus>,
+    ) -> Result<ReplayStatus, CommandError> {
+        let status = status.context(NoReplaySnafu)?;
+        self.sink.emit_all("replay", &status);
+
+        Ok(status)
+    }
+
+    pub fn list_feeds(&self) -> Vec<FeedInfo> {
+        self.feeds.list()
+    }
+
+    // Stop forwarding one app feed, the others keep running. Resolves once the feed is closed and its
+    // subscriber task finished
+    pub async fn stop_feed(&self, id: String) -> Result<FeedClosed, CommandError> {
+        let feed = self
+            .feeds
+            .detach(&id)
+            .context(UnknownFeedSnafu { id: &id })?;
+        self.forget_feed(&feed, &[]);
+        self.send_feeds();
+
+        Ok(close_feed(feed, CloseReason::Stopped, &*self.sink).await)
+    }
+
+    // Nothing is streamed on a closed feed and its topics are gone. What the session kept for its
+    // batches goes too, unless another feed or the feed replacing it still advertises them
+    fn forget_feed(&self, feed: &Feed, readvertised: &[String]) {
+        let id = feed.id();
+        self.router.forget_feed(id);
+        self.topic_inventory.remove(id);
+        self.sink
+            .emit_all("topic_inventory", self.topic_inventory.snapshot());
+
+        let gone: Vec<String> = feed
+            .batch_ids()
+            .iter()
+            .filter(|batch_id| !readvertised.contains(batch_id) && !self.feeds.advertised(batch_id))
+            .cloned()
+            .collect();
+        self.session.forget_batches(&gone);
+    }
+
+    // Feed list and the strategies of every feed as one strategy list
+    fn send_feeds(&self) {
+        self.sink.emit_all("feeds", self.feeds.list());
+
+        let strategy_list = self.feeds.strategy_list();
+        self.recorder.record_strategy_list(&strategy_list);
+        self.sink.emit_all("strategy_list", strategy_list);
+    }
+
+    pub async fn app_request(&self) -> Result<(), CommandError> {
+        self.health_monitor.app_requested();
+
+        // Frontend asks as soon as it loads, make sure we can hear the response first
+        let connection = self.service_link.connected().await;
+        let result = connection
+            .publishers
+            .app_request
+            .publish(self.service_link.app_request())
+            .await;
+
+        self.service_link.report("app request", result)
+    }
+
+    // Ask for the app response and resolve once one was handled, so every feed services advertise is
+    // followed. Nothing times out, callers that can't wait forever have to
+    pub async fn follow_advertised(&self) -> Result<(), CommandError> {
+        let mut handled = self.app_responses.subscribe();
+        handled.borrow_and_update();
+        self.app_request().await?;
+
+        // The sender lives as long as the core
+        let _ = handled.changed().await;
+        Ok(())
+    }
+
+    // Resolves once the chart data is back. The window shows the chart from now on. An empty
+    // request clears the chart so nothing is waited for
+    pub async fn chart_request(
+        &self,
+        label: &str,
+        batch_id: String,
+        strategy_id: String,
+        symbol: String,
+        period_s: u32,
+    ) -> Result<(), CommandError> {
+        let clear = strategy_id.is_empty();
+        let key = format!("{}/{}/{}/{}", batch_id, strategy_id, symbol, period_s);
+
+        let scope = WindowScope {
+            batch_id: batch_id.clone(),
+            strategy_id: strategy_id.clone(),
+            symbol: symbol.clone(),
+            period_s,
+        };
+        if clear {
+            self.router.clear_scope(label);
+        } else {
+            self.router.set_scope(label, scope.clone());
+        }
+
+        let service_link = &self.service_link;
+        let router = &self.router;
+        let sink = &*self.sink;
+        let feed_ids = self.feeds.for_batch(&batch_id);
+        let answered_on = feed_ids.clone();
+        let publish = move |timestamp_ns| async move {
+            let connection = service_link.connection().await?;
+            let result = connection
+                .publishers
+                .chart_request
+                .publish(ChartRequest {
+                    timestamp_ns,
+                    batch_id,
+                    strategy_id,
+                    symbol,
+                    period_s,
+                })
+                .await;
+
+            service_link.report("chart request", result)?;
+            let replaced = router.streaming(&feed_ids, (!clear).then_some(scope));
+            send_chart_stale(router, &replaced, sink);
+
+            Ok(())
+        };
+
+        if clear {
+            return publish(identity::timestamp_ns()).await;
+        }
+
+        self.outstanding_requests
+            .request(RequestKind::Chart, key, answered_on, publish)
+            .await
+    }
+
+    // Resolves once the overall stats for the batch are back
+    pub async fn overall_request(&self, batch_id: String) -> Result<(), CommandError> {
+        error::require("batch id", &batch_id)?;
+        self.session.overall_batch(&batch_id);
+        self.recorder.record_overall_batch(&batch_id);
+
+        // Overall views mount together and all ask for the same batch, only the first one is published
+        let service_link = &self.service_link;
+        self.outstanding_requests
+            .request(
+                RequestKind::Overall,
+                batch_id.clone(),
+                self.feeds.for_batch(&batch_id),
+                |timestamp_ns| async move {
+                    let connection = service_link.connection().await?;
+                    let result = connection
+                        .publishers
+                        .overall_request
+                        .publish(OverallRequest {
+                            timestamp_ns,
+                            batch_id,
+                        })
+                        .await;
+
+                    service_link.report("overall request", result)
+                },
+            )
+            .await
+    }
+
+    // Ask services to read the logs in a directory, they answer with a read from dir response
+    pub async fn read_from_dir(&self, log_dir: &Path) -> Result<(), CommandError> {
+        let connection = self.service_link.connection().await?;
+        let result = connection
+            .publishers
+            .read_from_dir_request
+            .publish(ReadFromDirRequest {
+                timestamp_ns: identity::timestamp_ns(),
+                log_dir: log_dir.display().to_string(),
+            })
+            .await;
+        self.service_link.report("read from dir request", result)?;
+
+        self.sink.emit_all("loading", ());
+
+        Ok(())
+    }
+
+    // Resolves once the chart data read from the log is back. The window shows the chart from now on
+    pub async fn strategy_from_log_request(
+        &self,
+        label: &str,
+        batch_id: String,
+        strategy_id: String,
+        symbol: String,
+        period_s: u32,
+    ) -> Result<(), CommandError> {
+        error::require("strategy id", &strategy_id)?;
+        error::require("symbol", &symbol)?;
+        if period_s == 0 {
+            return InvalidArgumentSnafu {
+                name: "period",
+                reason: "must be greater than 0",
+            }
+            .fail();
+        }
+
+        let key = format!("{}/{}/{}/{}", batch_id, strategy_id, symbol, period_s);
+
+        let scope = WindowScope {
+            batch_id: batch_id.clone(),
+            strategy_id: strategy_id.clone(),
+            symbol: symbol.clone(),
+            period_s,
+        };
+        self.router.set_scope(label, scope.clone());
+
+        let service_link = &self.service_link;
+        let router = &self.router;
+        let sink = &*self.sink;
+        let feed_ids = self.feeds.for_batch(&batch_id);
+        self.outstanding_requests
+            .request(
+                RequestKind::Chart,
+                key,
+                feed_ids.clone(),
+                |timestamp_ns| async move {
+                    let connection = service_link.connection().await?;
+                    let result = connection
+                        .publishers
+                        .strategy_from_log_request
+                        .publish(StrategyFromLogRequest {
+                            timestamp_ns,
+                            batch_id,
+                            strategy_id,
+                            symbol,
+                            period_s,
+                        })
+                        .await;
+
+                    service_link.report("strategy from log request", result)?;
+                    let replaced = router.streaming(&feed_ids, Some(scope));
+                    send_chart_stale(router, &replaced, sink);
+
+                    Ok(())
+                },
+            )
+            .await
+    }
+
+    // Resolves once the overall stats read from the log are back
+    pub async fn overall_from_log_request(&self, batch_id: String) -> Result<(), CommandError> {
+        error::require("batch id", &batch_id)?;
+        self.session.overall_batch(&batch_id);
+        self.recorder.record_overall_batch(&batch_id);
+
+        let service_link = &self.service_link;
+        self.outstanding_requests
+            .request(
+                RequestKind::Overall,
+                batch_id.clone(),
+                self.feeds.for_batch(&batch_id),
+                |timestamp_ns| async move {
+                    let connection = service_link.connection().await?;
+                    let result = connection
+                        .publishers
+                        .overall_from_log_request
+                        .publish(OverallFromLogRequest {
+                            timestamp_ns,
+                            batch_id,
+                        })
+                        .await;
+
+                    service_link.report("overall from log request", result)
+                },
+            )
+            .await
+    }
+
+    // Run a strategy yaml. Returns the path of the yaml that was run
+    pub async fn run_yaml(&self, yaml: &Path) -> Result<String, CommandError> {
+        let connection = self.service_link.connection().await?;
+
+        // The run advertises its own feed in an app response, feeds of other runs keep going
+        let yaml_path = yaml.display().to_string();
+        let result = connection
+            .publishers
+            .run_yaml
+            .publish(RunYaml {
+                timestamp_ns: identity::timestamp_ns(),
+                yaml_path: yaml_path.clone(),
+            })
+            .await;
+        self.service_link.report("run yaml", result)?;
+
+        self.sink.emit_all("loading", ());
+
+        Ok(yaml_path)
+    }
+}
+
+// Close a feed that was taken out of AppFeeds and tell the frontend it ended
+async fn close_feed(feed: Feed, reason: CloseReason, sink: &dyn EventSink) -> FeedClosed {
+    let closed = feed.close(reason).await;
+    sink.emit_all("feed_closed", &closed);
+
+    closed
+}
+
+// Windows showing a chart that isn't streamed anymore are told so they can show it is not live
+fn send_chart_stale(router: &WindowRouter, replaced: &[WindowScope], sink: &dyn EventSink) {
+    for scope in replaced {
+        let windows = router.showing(scope);
+        if !windows.is_empty() {
+            sink.emit_to("chart_stale", &Target::Windows(windows), scope);
+        }
+    }
+}
+
+fn send_feed_error(error: FeedError, sink: &dyn EventSink) {
+    eprintln!("Can't follow app feed: {}", error);
+    sink.emit_all("feed_error", &error);
+}
+
+// Listens for service responses and follows the app feeds they advertise
+pub struct AppServiceSubscriber {
+    core: Arc<AppCore>,
+    last_response: Option<AppResponse>,
+}
+
+impl AppServiceSubscriber {
+    pub fn new(core: Arc<AppCore>) -> Self {
+        Self {
+            core,
+            last_response: None,
+        }
+    }
+}
+
+#[async_trait]
+impl Subscribe<ReadFromDirResponse> for AppServiceSubscriber {
+    async fn on_data(&mut self, msg: ReadFromDirResponse) -> Result<(), SubscriberError> {
+        self.core.health_monitor.seen(BackendService::App);
+        self.core.sink.emit_all("strategy_list", &msg);
+        self.core.sink.emit_all("open_log_successful", &msg);
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Subscribe<AppResponse> for AppServiceSubscriber {
+    async fn on_data(&mut self, msg: AppResponse) -> Result<(), SubscriberError> {
+        let result = self.follow(msg).await;
+        self.core.app_responses.send_modify(|handled| *handled += 1);
+        result
+    }
+}
+
+impl AppServiceSubscriber {
+    async fn follow(&mut self, msg: AppResponse) -> Result<(), SubscriberError> {
+        let core = &self.core;
+
+        // Heartbeats and other app instances get the same response, nothing to do if it didn't change
+        let requested = core.health_monitor.app_response();
+        if !requested
+            && self
+                .last_response
+                .as_ref()
+                .is_some_and(|last| feeds::same_advertisement(last, &msg))
+        {
+            return Ok(());
+        }
+
+        // When we receive the app response, we can subscribe to the app feed so we can visualize.
+        // A new response on an endpoint we already follow replaces that feed
+        let endpoint = match feeds::feed_endpoint(&msg) {
+            Ok(endpoint) => endpoint,
+            Err(e) => {
+                // Same response would fail the same way, only report it once
+                send_feed_error(e, &*core.sink);
+                self.last_response = Some(msg);
+                return Ok(());
+            }
+        };
+
+        // Responses advertising other feeds can come in between, a feed that is still followed from
+        // the same advertisement is kept
+        let feed_id = endpoint.to_string();
+        if !requested && core.feeds.advertises(&feed_id, &msg) {
+            self.last_response = Some(msg);
+            return Ok(());
+        }
+
+        let batch_ids: Vec<String> = msg.batches.iter().map(|b| b.batch_id.clone()).collect();
+        if let Some(feed) = core.feeds.detach(&feed_id) {
+            core.forget_feed(&feed, &batch_ids);
+            close_feed(feed, CloseReason::Replaced, &*core.sink).await;
+        }
+
+        let (finished_tx, finished_rx) = oneshot::channel();
+        let app_subscriber = AppSubscriber::new(core, feed_id.clone(), batch_ids, finished_tx);
+        let (node, mut subscriber) = match connect_feed(endpoint, app_subscriber).await {
+            Ok(connected) => connected,
+            Err(e) => {
+                send_feed_error(e, &*core.sink);
+                return Ok(());
+            }
+        };
+
+        // Subscribe to topics we need to get the data we need to visualize
+        let mut inventory = Vec::with_capacity(msg.topics.len());
+        for topic in &msg.topics {
+            let Ok(mtype) = MessageType::try_from(topic.mtype) else {
+                eprintln!(
+                    "Unknown message type {} on topic {}, ignoring it",
+                    topic.mtype, topic.topic
+                );
+                inventory.push(AdvertisedTopic::new(
+                    &feed_id,
+                    &topic.topic,
+                    topic.mtype,
+                    TopicStatus::UnknownType,
+                ));
+                continue;
+            };
+
+            let advertised = match core
+                .registry
+                .subscribe(mtype, &node, &topic.topic, &mut subscriber)
+                .await
+            {
+                Some(Ok(())) => AdvertisedTopic::new(
+                    &feed_id,
+                    &topic.topic,
+                    topic.mtype,
+                    TopicStatus::Subscribed,
+                ),
+                Some(Err(e)) => {
+                    eprintln!("Failed to subscribe to {}: {}", topic.topic, e);
+                    AdvertisedTopic {
+                        error: Some(e.to_string()),
+                        ..AdvertisedTopic::new(
+                            &feed_id,
+                            &topic.topic,
+                            topic.mtype,
+                            TopicStatus::Failed,
+                        )
+                    }
+                }
+                None => {
+                    eprintln!(
+                        "No handler for {:?} on topic {}, ignoring it",
+                        mtype, topic.topic
+                    );
+                    AdvertisedTopic::new(
+                        &feed_id,
+                        &topic.topic,
+                        topic.mtype,
+                        TopicStatus::Unsupported,
+                    )
+                }
+            };
+            inventory.push(advertised);
+        }
+
+        core.topic_inventory.set(&feed_id, inventory);
+
+        let feed = Feed::new(
+            feed_id,
+            &msg,
+            node,
+            Box::new(subscriber),
+            finished_rx,
+            identity::timestamp_ns(),
+        );
+        if let Some(replaced) = core.feeds.attach(feed) {
+            close_feed(replaced, CloseReason::Replaced, &*core.sink).await;
+        }
+
+        core.sink
+            .emit_all("topic_inventory", core.topic_inventory.snapshot());
+
+        core.send_feeds();
+        self.last_response = Some(msg);
+
+        Ok(())
+    }
+}
+
+async fn connect_feed(
+    endpoint: SocketAddr,
+    app_subscriber: AppSubscriber,
+) -> Result<(Node, Subscriber<AppSubscriber>), FeedError> {
+    let node = create_node(&endpoint.ip().to_string(), endpoint.port())
+        .await
+        .context(ConnectSnafu { endpoint })?;
+    let subscriber = node
+        .new_subscriber(app_subscriber)
+        .await
+        .context(ConnectSnafu { endpoint })?;
+
+    Ok((node, subscriber))
+}
+
+#[inline]
+async fn create_node(ip: &str, port: u16) -> Result<Node, NodeError> {
+    let mut node_builder = NodeBuilder::new();
+    node_builder.set_network((ip.to_string(), port));
+
+    let node = node_builder.build().await?;
+
+    Ok(node)
+}
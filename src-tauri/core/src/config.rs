@@ -0,0 +1,428 @@
+use serde::ser::SerializeStruct;
+use serde::{Deserialize, Serialize, Serializer};
+use snafu::{ResultExt, Snafu};
+use std::collections::HashMap;
+use std::env;
+use std::fmt::{self, Write};
+use std::net::{IpAddr, Ipv4Addr};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::topics::{Topic, TopicConfig};
+
+// Environment variable that can point the app at a service config when no --config arg is given
+pub const SERVICE_CONFIG_ENV: &str = "TRADEBOT_APP_CONFIG";
+
+// File name looked up in the platform config dir, e.g. ~/.config/com.tradebot-app.app/service.yml
+pub const SERVICE_CONFIG_FILE: &str = "service.yml";
+
+const DEFAULT_HEARTBEAT_INTERVAL_MS: u64 = 5000;
+const DEFAULT_REQUEST_TIMEOUT_MS: u64 = 10000;
+const DEFAULT_FRAME_INTERVAL_MS: u64 = 16;
+
+// Config shipped with the app so packaged builds always have something to fall back on
+const DEFAULT_SERVICE_CONFIG: &str = include_str!("../config/service.yml");
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct ServiceConfig {
+    pub zenoh_config_path: Option<String>,
+    pub ip: String,
+    pub port: u16,
+    pub topics: Option<HashMap<String, String>>,
+    pub heartbeat_interval_ms: Option<u64>,
+    pub request_timeout_ms: Option<u64>,
+    pub client_id: Option<String>,
+    pub frame_interval_ms: Option<u64>,
+    pub recording_dir: Option<String>,
+}
+
+#[derive(Debug, Snafu)]
+#[snafu(visibility(pub))]
+pub enum ConfigError {
+    #[snafu(display("Failed to read service config {}: {}", config, source))]
+    ReadConfig {
+        config: String,
+        source: std::io::Error,
+    },
+
+    #[snafu(display("Failed to parse service config {}: {}", config, source))]
+    ParseConfig {
+        config: String,
+        source: serde_yaml::Error,
+    },
+
+    #[snafu(display("Service ip '{}' is not a valid IP address", ip))]
+    InvalidIp { ip: String },
+
+    #[snafu(display("Service ip {} cannot be used to reach TradeBot services", ip))]
+    UnusableIp { ip: IpAddr },
+
+    #[snafu(display("Service port {} is out of range, must be between 1 and 65535", port))]
+    InvalidPort { port: u16 },
+
+    #[snafu(display("Heartbeat interval must be greater than 0"))]
+    InvalidHeartbeatInterval,
+
+    #[snafu(display("Request timeout must be greater than 0"))]
+    InvalidRequestTimeout,
+
+    #[snafu(display("Frame interval must be greater than 0"))]
+    InvalidFrameInterval,
+
+    #[snafu(display("Zenoh config path {} does not exist", path))]
+    ZenohConfigNotFound { path: String },
+
+    #[snafu(display("Unknown topic key '{}', it will be ignored", key))]
+    UnknownTopic { key: String },
+}
+
+impl ConfigError {
+    // Short machine readable name so the frontend can tell errors apart
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::ReadConfig { .. } => "read_config",
+            Self::ParseConfig { .. } => "parse_config",
+            Self::InvalidIp { .. } => "invalid_ip",
+            Self::UnusableIp { .. } => "unusable_ip",
+            Self::InvalidPort { .. } => "invalid_port",
+            Self::InvalidHeartbeatInterval => "invalid_heartbeat_interval",
+            Self::InvalidRequestTimeout => "invalid_request_timeout",
+            Self::InvalidFrameInterval => "invalid_frame_interval",
+            Self::ZenohConfigNotFound { .. } => "zenoh_config_not_found",
+            Self::UnknownTopic { .. } => "unknown_topic",
+        }
+    }
+
+    // Fatal errors stop the app from connecting to services, everything else is just reported
+    pub fn is_fatal(&self) -> bool {
+        !matches!(self, Self::UnknownTopic { .. })
+    }
+}
+
+impl Serialize for ConfigError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("ConfigError", 3)?;
+        state.serialize_field("kind", self.kind())?;
+        state.serialize_field("message", &self.to_string())?;
+        state.serialize_field("fatal", &self.is_fatal())?;
+        state.end()
+    }
+}
+
+// Sent to the frontend when the app could not start because of a bad service config
+#[derive(Debug, Serialize)]
+pub struct StartupError {
+    pub config: String,
+    pub errors: Vec<ConfigError>,
+}
+
+impl ServiceConfig {
+    // Read, parse and validate the service config. Non fatal problems are logged and otherwise ignored
+    pub fn load(source: &ServiceConfigSource) -> Result<Self, StartupError> {
+        let config = source.to_string();
+        let mut service_config = source
+            .read()
+            .context(ReadConfigSnafu {
+                config: config.clone(),
+            })
+            .and_then(|f| {
+                serde_yaml::from_str::<ServiceConfig>(&f).context(ParseConfigSnafu {
+                    config: config.clone(),
+                })
+            })
+            .map_err(|e| StartupError {
+                config: config.clone(),
+                errors: vec![e],
+            })?;
+        if let Some(dir) = source.dir() {
+            service_config.resolve_paths(dir);
+        }
+
+        let (fatal, warnings): (Vec<_>, Vec<_>) = service_config
+            .validate()
+            .into_iter()
+            .partition(ConfigError::is_fatal);
+
+        for warning in &warnings {
+            eprintln!("Service config {}: {}", config, warning);
+        }
+
+        if fatal.is_empty() {
+            Ok(service_config)
+        } else {
+            Err(StartupError {
+                config,
+                errors: fatal.into_iter().chain(warnings).collect(),
+            })
+        }
+    }
+
+    // Check the config for anything that would stop the app from reaching TradeBot services
+    pub fn validate(&self) -> Vec<ConfigError> {
+        let mut errors = Vec::new();
+
+        match self.ip.parse::<IpAddr>() {
+            Ok(ip) if !usable_ip(ip) => errors.push(ConfigError::UnusableIp { ip }),
+            Ok(_) => (),
+            Err(_) => errors.push(ConfigError::InvalidIp {
+                ip: self.ip.clone(),
+            }),
+        }
+
+        if self.port == 0 {
+            errors.push(ConfigError::InvalidPort { port: self.port });
+        }
+
+        if self.heartbeat_interval_ms == Some(0) {
+            errors.push(ConfigError::InvalidHeartbeatInterval);
+        }
+
+        if self.request_timeout_ms == Some(0) {
+            errors.push(ConfigError::InvalidRequestTimeout);
+        }
+
+        if self.frame_interval_ms == Some(0) {
+            errors.push(ConfigError::InvalidFrameInterval);
+        }
+
+        if let Some(path) = &self.zenoh_config_path {
+            if !Path::new(path).is_file() {
+                errors.push(ConfigError::ZenohConfigNotFound { path: path.clone() });
+            }
+        }
+
+        if let Some(topics) = &self.topics {
+            let (_, unknown) = TopicConfig::from_config(topics);
+            errors.extend(
+                unknown
+                    .into_iter()
+                    .map(|key| ConfigError::UnknownTopic { key }),
+            );
+        }
+
+        errors
+    }
+
+    // Relative paths in a config file are relative to the file, not to wherever the app was started
+    fn resolve_paths(&mut self, dir: &Path) {
+        for path in [&mut self.zenoh_config_path, &mut self.recording_dir]
+            .into_iter()
+            .flatten()
+        {
+            if Path::new(path).is_relative() {
+                *path = dir.join(&*path).display().to_string();
+            }
+        }
+    }
+
+    pub fn heartbeat_interval(&self) -> Duration {
+        Duration::from_millis(
+            self.heartbeat_interval_ms
+                .unwrap_or(DEFAULT_HEARTBEAT_INTERVAL_MS),
+        )
+    }
+
+    // How long a chart or overall request waits for its answer
+    pub fn request_timeout(&self) -> Duration {
+        Duration::from_millis(
+            self.request_timeout_ms
+                .unwrap_or(DEFAULT_REQUEST_TIMEOUT_MS),
+        )
+    }
+
+    // How often batched app feed events are flushed to the frontend
+    pub fn frame_interval(&self) -> Duration {
+        Duration::from_millis(self.frame_interval_ms.unwrap_or(DEFAULT_FRAME_INTERVAL_MS))
+    }
+
+    // Where recordings of app feeds are written, the recordings dir in the app data dir by default.
+    // None if neither is there, recording fails then
+    pub fn recording_dir(&self, data_dir: Option<PathBuf>) -> Option<PathBuf> {
+        match &self.recording_dir {
+            Some(dir) => Some(PathBuf::from(dir)),
+            None => data_dir.map(|dir| dir.join("recordings")),
+        }
+    }
+
+    // Topic names to use on the service node, falling back to the defaults for anything not mapped
+    pub fn topic_config(&self) -> TopicConfig {
+        self.topics
+            .as_ref()
+            .map(|topics| TopicConfig::from_config(topics).0)
+            .unwrap_or_default()
+    }
+
+    // Complete service config with every topic the app uses set to its default name
+    pub fn template() -> String {
+        let mut template = String::from("---\n#zenoh-config-path: \"path\"\nip: 224.0.0.224\nport: 7441\n#heartbeat-interval-ms: 5000\n#request-timeout-ms: 10000\n#client-id: \"id\"\n#frame-interval-ms: 16\n#recording-dir: \"path\"\ntopics:\n");
+        for topic in Topic::ALL {
+            writeln!(template, "    {}: {}", topic.key(), topic.default_name()).unwrap();
+        }
+
+        template
+    }
+}
+
+// Whether services can be reached on the address, checked for the configured ip and for the ones
+// app feeds are advertised on
+pub fn usable_ip(ip: IpAddr) -> bool {
+    !ip.is_unspecified() && ip != IpAddr::V4(Ipv4Addr::BROADCAST)
+}
+
+// Where the service config was found. Sources are checked in the order of the variants
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ServiceConfigSource {
+    CliArg(PathBuf),
+    EnvVar(PathBuf),
+    ConfigDir(PathBuf),
+    Bundled,
+}
+
+impl ServiceConfigSource {
+    // Resolve the service config from the --config arg, then the environment, then the platform
+    // config dir and finally the config bundled with the app
+    pub fn resolve(cli_path: Option<PathBuf>, config_dir: Option<PathBuf>) -> Self {
+        if let Some(path) = cli_path {
+            return Self::CliArg(path);
+        }
+
+        if let Some(path) = env::var_os(SERVICE_CONFIG_ENV).filter(|p| !p.is_empty()) {
+            return Self::EnvVar(PathBuf::from(path));
+        }
+
+        if let Some(path) = config_dir
+            .map(|dir| dir.join(SERVICE_CONFIG_FILE))
+            .filter(|path| path.is_file())
+        {
+            return Self::ConfigDir(path);
+        }
+
+        Self::Bundled
+    }
+
+    // Directory of the config file, None for the bundled config
+    pub fn dir(&self) -> Option<&Path> {
+        match self {
+            Self::CliArg(path) | Self::EnvVar(path) | Self::ConfigDir(path) => path.parent(),
+            Self::Bundled => None,
+        }
+    }
+
+    pub fn read(&self) -> std::io::Result<String> {
+        match self {
+            Self::CliArg(path) | Self::EnvVar(path) | Self::ConfigDir(path) => {
+                std::fs::read_to_string(path)
+            }
+            Self::Bundled => Ok(DEFAULT_SERVICE_CONFIG.to_owned()),
+        }
+    }
+}
+
+impl fmt::Display for ServiceConfigSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::CliArg(path) => write!(f, "{} (--config)", path.display()),
+            Self::EnvVar(path) => write!(f, "{} (${})", path.display(), SERVICE_CONFIG_ENV),
+            Self::ConfigDir(path) => write!(f, "{}", path.display()),
+            Self::Bundled => write!(f, "bundled default"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(ip: &str, port: u16) -> ServiceConfig {
+        serde_yaml::from_str(&format!("ip: \"{}\"\nport: {}\n", ip, port)).unwrap()
+    }
+
+    fn kinds(errors: &[ConfigError]) -> Vec<&'static str> {
+        errors.iter().map(ConfigError::kind).collect()
+    }
+
+    #[test]
+    fn valid_config_has_no_errors() {
+        assert!(config("224.0.0.224", 7441).validate().is_empty());
+    }
+
+    #[test]
+    fn invalid_ip_is_fatal() {
+        let errors = config("not an ip", 7441).validate();
+        assert_eq!(kinds(&errors), vec!["invalid_ip"]);
+        assert!(errors[0].is_fatal());
+    }
+
+    #[test]
+    fn unspecified_and_broadcast_ips_are_unusable() {
+        for ip in ["0.0.0.0", "::", "255.255.255.255"] {
+            assert_eq!(
+                kinds(&config(ip, 7441).validate()),
+                vec!["unusable_ip"],
+                "{}",
+                ip
+            );
+        }
+    }
+
+    #[test]
+    fn port_0_is_invalid() {
+        assert_eq!(
+            kinds(&config("127.0.0.1", 0).validate()),
+            vec!["invalid_port"]
+        );
+    }
+
+    #[test]
+    fn missing_zenoh_config_is_fatal() {
+        let service_config = ServiceConfig {
+            zenoh_config_path: Some("/nonexistent/zenoh.json5".to_owned()),
+            ..config("127.0.0.1", 7441)
+        };
+
+        let errors = service_config.validate();
+        assert_eq!(kinds(&errors), vec!["zenoh_config_not_found"]);
+        assert!(errors[0].is_fatal());
+    }
+
+    #[test]
+    fn unknown_topic_is_only_a_warning() {
+        let service_config = ServiceConfig {
+            topics: Some(HashMap::from([(
+                "no-such-topic".to_owned(),
+                "topic".to_owned(),
+            )])),
+            ..config("127.0.0.1", 7441)
+        };
+
+        let errors = service_config.validate();
+        assert_eq!(kinds(&errors), vec!["unknown_topic"]);
+        assert!(!errors[0].is_fatal());
+    }
+
+    #[test]
+    fn relative_paths_resolve_against_the_config_file() {
+        let dir = env::temp_dir().join(format!("tradebot-app-config-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("zenoh.json5"), "{}").unwrap();
+        let path = dir.join(SERVICE_CONFIG_FILE);
+        std::fs::write(
+            &path,
+            "ip: 127.0.0.1\nport: 7447\nzenoh-config-path: zenoh.json5\nrecording-dir: /tmp/recordings\n",
+        )
+        .unwrap();
+
+        let service_config = ServiceConfig::load(&ServiceConfigSource::CliArg(path)).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(
+            service_config.zenoh_config_path,
+            Some(dir.join("zenoh.json5").display().to_string())
+        );
+        assert_eq!(
+            service_config.recording_dir,
+            Some("/tmp/recordings".to_owned())
+        );
+    }
+}
@@ -0,0 +1,95 @@
+use serde::ser::SerializeStruct;
+use serde::{Serialize, Serializer};
+use snafu::Snafu;
+use zenoh_node::error::NodeError;
+
+// Error returned to the frontend by commands, so invokes reject with something actionable
+#[derive(Debug, Snafu)]
+#[snafu(visibility(pub))]
+pub enum CommandError {
+    #[snafu(display("Failed to publish {}: {}", request, source))]
+    Publish {
+        request: &'static str,
+        source: NodeError,
+    },
+
+    #[snafu(display("Invalid {}: {}", name, reason))]
+    InvalidArgument { name: &'static str, reason: String },
+
+    #[snafu(display("No {} was selected", selection))]
+    DialogCancelled { selection: &'static str },
+
+    #[snafu(display("Not connected to TradeBot services"))]
+    NotConnected,
+
+    #[snafu(display("No answer to {} within {}ms", request, timeout_ms))]
+    Timeout {
+        request: &'static str,
+        timeout_ms: u64,
+    },
+
+    // Another invoke published the request this one joined and that failed
+    #[snafu(display("{}", message))]
+    RequestFailed { kind: &'static str, message: String },
+
+    #[snafu(display("Failed to open window {}: {}", label, source))]
+    OpenWindow {
+        label: String,
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+
+    #[snafu(display("No app feed {}", id))]
+    UnknownFeed { id: String },
+
+    #[snafu(display("Recording failed: {}", source))]
+    Recording { source: std::io::Error },
+
+    #[snafu(display("Failed to open recording {}: {}", path, source))]
+    Replay {
+        path: String,
+        source: std::io::Error,
+    },
+
+    #[snafu(display("No recording is open for replay"))]
+    NoReplay,
+}
+
+impl CommandError {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Publish { .. } => "publish",
+            Self::InvalidArgument { .. } => "invalid_argument",
+            Self::DialogCancelled { .. } => "dialog_cancelled",
+            Self::NotConnected => "not_connected",
+            Self::Timeout { .. } => "timeout",
+            Self::RequestFailed { kind, .. } => kind,
+            Self::OpenWindow { .. } => "open_window",
+            Self::UnknownFeed { .. } => "unknown_feed",
+            Self::Recording { .. } => "recording",
+            Self::Replay { .. } => "replay",
+            Self::NoReplay => "no_replay",
+        }
+    }
+}
+
+impl Serialize for CommandError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("CommandError", 2)?;
+        state.serialize_field("kind", self.kind())?;
+        state.serialize_field("message", &self.to_string())?;
+        state.end()
+    }
+}
+
+// Reject empty string arguments
+pub fn require(name: &'static str, value: &str) -> Result<(), CommandError> {
+    if value.is_empty() {
+        return InvalidArgumentSnafu {
+            name,
+            reason: "must not be empty",
+        }
+        .fail();
+    }
+
+    Ok(())
+}
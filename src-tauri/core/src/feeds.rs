@@ -0,0 +1,364 @@
+use serde::ser::SerializeStruct;
+use serde::{Serialize, Serializer};
+use snafu::Snafu;
+use std::collections::BTreeMap;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::sync::oneshot;
+use tradebot_protos::messages::AppResponse;
+use zenoh_node::error::NodeError;
+use zenoh_node::node::{Abort, Node};
+
+use crate::config;
+
+// How long closing a feed waits for its subscriber task to finish
+const FEED_CLOSE_TIMEOUT: Duration = Duration::from_secs(5);
+
+// Why an app feed advertised in an app response could not be followed, sent to the frontend as the
+// feed_error event
+#[derive(Debug, Snafu)]
+#[snafu(visibility(pub))]
+pub enum FeedError {
+    #[snafu(display("App response has no network endpoint for the app feed"))]
+    MissingNetwork,
+
+    #[snafu(display("App feed ip '{}' is not a valid IP address", ip))]
+    InvalidIp { ip: String },
+
+    #[snafu(display("App feed ip {} cannot be used to reach the app feed", ip))]
+    UnusableIp { ip: IpAddr },
+
+    #[snafu(display("App feed port {} is out of range, must be between 1 and 65535", port))]
+    InvalidPort { port: u32 },
+
+    #[snafu(display("Failed to connect to app feed {}: {}", endpoint, source))]
+    Connect {
+        endpoint: SocketAddr,
+        source: NodeError,
+    },
+}
+
+impl FeedError {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::MissingNetwork => "missing_network",
+            Self::InvalidIp { .. } => "invalid_ip",
+            Self::UnusableIp { .. } => "unusable_ip",
+            Self::InvalidPort { .. } => "invalid_port",
+            Self::Connect { .. } => "connect",
+        }
+    }
+}
+
+impl Serialize for FeedError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("FeedError", 2)?;
+        state.serialize_field("kind", self.kind())?;
+        state.serialize_field("message", &self.to_string())?;
+        state.end()
+    }
+}
+
+// Endpoint of the app feed advertised in an app response, checked the same way as the service
+// endpoint in the service config
+pub fn feed_endpoint(response: &AppResponse) -> Result<SocketAddr, FeedError> {
+    let network = response.network.as_ref().ok_or(FeedError::MissingNetwork)?;
+
+    let ip = match network.ip.parse::<IpAddr>() {
+        Ok(ip) if !config::usable_ip(ip) => return UnusableIpSnafu { ip }.fail(),
+        Ok(ip) => ip,
+        Err(_) => {
+            return InvalidIpSnafu {
+                ip: network.ip.clone(),
+            }
+            .fail();
+        }
+    };
+
+    // Proto only has u32, anything that doesn't fit a u16 would silently wrap
+    let port = match u16::try_from(network.port) {
+        Ok(port) if port > 0 => port,
+        _ => return InvalidPortSnafu { port: network.port }.fail(),
+    };
+
+    Ok(SocketAddr::new(ip, port))
+}
+
+// Whether two app responses advertise the same feed. Services stamp every response they send, so
+// the timestamp doesn't count
+pub fn same_advertisement(a: &AppResponse, b: &AppResponse) -> bool {
+    a.network == b.network && a.topics == b.topics && a.batches == b.batches
+}
+
+// What the frontend gets to know about an app feed
+#[derive(Debug, Clone, Serialize)]
+pub struct FeedInfo {
+    // Network endpoint the feed is subscribed on, also what identifies the feed
+    pub id: String,
+    pub batch_ids: Vec<String>,
+    pub topics: usize,
+    pub started_ns: u64,
+}
+
+// Why a feed was closed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CloseReason {
+    // A new app response advertised the same endpoint
+    Replaced,
+    // Stopped from the UI with stop_feed
+    Stopped,
+}
+
+// Payload of the feed_closed event
+#[derive(Debug, Clone, Serialize)]
+pub struct FeedClosed {
+    pub id: String,
+    pub reason: CloseReason,
+    // False if the subscriber task was still running when we stopped waiting for it
+    pub finished: bool,
+}
+
+// Node and subscriptions a service advertised in an app response
+pub struct Feed {
+    info: FeedInfo,
+    // App response the feed was followed from
+    advertisement: AppResponse,
+    node: Node,
+    subscriber: Box<dyn Abort>,
+    finished: oneshot::Receiver<()>,
+}
+
+impl Feed {
+    pub fn new(
+        id: String,
+        response: &AppResponse,
+        node: Node,
+        subscriber: Box<dyn Abort>,
+        finished: oneshot::Receiver<()>,
+        started_ns: u64,
+    ) -> Self {
+        Self {
+            info: FeedInfo {
+                id,
+                batch_ids: response
+                    .batches
+                    .iter()
+                    .map(|b| b.batch_id.clone())
+                    .collect(),
+                topics: response.topics.len(),
+                started_ns,
+            },
+            advertisement: response.clone(),
+            node,
+            subscriber,
+            finished,
+        }
+    }
+
+    pub fn id(&self) -> &str {
+        &self.info.id
+    }
+
+    pub fn batch_ids(&self) -> &[String] {
+        &self.info.batch_ids
+    }
+
+    // Abort the subscriptions, drop the node and wait for the subscriber task to finish
+    pub async fn close(self, reason: CloseReason) -> FeedClosed {
+        self.subscriber.abort();
+        drop(self.node);
+
+        // The sender is never sent on, the receiver resolves once it is dropped
+        let finished = tokio::time::timeout(FEED_CLOSE_TIMEOUT, self.finished)
+            .await
+            .is_ok();
+        if !finished {
+            eprintln!(
+                "Subscriber of feed {} still running after {}ms",
+                self.info.id,
+                FEED_CLOSE_TIMEOUT.as_millis()
+            );
+        }
+
+        FeedClosed {
+            id: self.info.id,
+            reason,
+            finished,
+        }
+    }
+}
+
+// Every app feed currently forwarded to the frontend, keyed by network endpoint so a live run and a
+// log replay, or several backtests, can be shown side by side
+#[derive(Default)]
+pub struct AppFeeds {
+    feeds: Mutex<BTreeMap<String, Feed>>,
+}
+
+impl AppFeeds {
+    pub fn new() -> Self {
+        Self {
+            feeds: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    // Add a feed. Returns the feed it replaced, if any, which still has to be closed
+    pub fn attach(&self, feed: Feed) -> Option<Feed> {
+        self.feeds
+            .lock()
+            .unwrap()
+            .insert(feed.info.id.clone(), feed)
+    }
+
+    // Take a feed out so it can be closed. Returns None if there is no feed with the id
+    pub fn detach(&self, id: &str) -> Option<Feed> {
+        self.feeds.lock().unwrap().remove(id)
+    }
+
+    // Whether the feed with the id was followed from the same advertisement as the response
+    pub fn advertises(&self, id: &str, response: &AppResponse) -> bool {
+        self.feeds
+            .lock()
+            .unwrap()
+            .get(id)
+            .is_some_and(|feed| same_advertisement(&feed.advertisement, response))
+    }
+
+    // Feeds a chart for the batch streams on, the ones advertising it. Batches read from logs aren't
+    // advertised by any feed, those could stream on every feed
+    pub fn for_batch(&self, batch_id: &str) -> Vec<String> {
+        let feeds = self.feeds.lock().unwrap();
+        let carrying: Vec<String> = feeds
+            .values()
+            .filter(|feed| feed.info.batch_ids.iter().any(|id| id == batch_id))
+            .map(|feed| feed.info.id.clone())
+            .collect();
+
+        if carrying.is_empty() {
+            feeds.keys().cloned().collect()
+        } else {
+            carrying
+        }
+    }
+
+    // Whether any feed advertises the batch
+    pub fn advertised(&self, batch_id: &str) -> bool {
+        self.feeds
+            .lock()
+            .unwrap()
+            .values()
+            .any(|feed| feed.info.batch_ids.iter().any(|id| id == batch_id))
+    }
+
+    pub fn list(&self) -> Vec<FeedInfo> {
+        self.feeds
+            .lock()
+            .unwrap()
+            .values()
+            .map(|feed| feed.info.clone())
+            .collect()
+    }
+
+    // Batches of every feed as one strategy list for the frontend
+    pub fn strategy_list(&self) -> AppResponse {
+        let batches = self
+            .feeds
+            .lock()
+            .unwrap()
+            .values()
+            .flat_map(|feed| feed.advertisement.batches.iter().cloned())
+            .collect();
+
+        AppResponse {
+            batches,
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tradebot_protos::messages::Network;
+
+    fn response(ip: &str, port: u32) -> AppResponse {
+        AppResponse {
+            network: Some(Network {
+                ip: ip.to_owned(),
+                port,
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn valid_endpoint() {
+        let endpoint = feed_endpoint(&response("127.0.0.1", 7447)).unwrap();
+        assert_eq!(endpoint.to_string(), "127.0.0.1:7447");
+
+        let endpoint = feed_endpoint(&response("::1", 65535)).unwrap();
+        assert_eq!(endpoint.to_string(), "[::1]:65535");
+    }
+
+    #[test]
+    fn missing_network() {
+        let error = feed_endpoint(&AppResponse::default()).unwrap_err();
+        assert!(matches!(error, FeedError::MissingNetwork));
+    }
+
+    #[test]
+    fn bad_ip() {
+        for ip in ["", "localhost", "127.0.0", "300.0.0.1", "127.0.0.1:7447"] {
+            let error = feed_endpoint(&response(ip, 7447)).unwrap_err();
+            assert!(matches!(error, FeedError::InvalidIp { .. }), "{}", ip);
+        }
+    }
+
+    #[test]
+    fn unusable_ip() {
+        for ip in ["0.0.0.0", "::", "255.255.255.255"] {
+            let error = feed_endpoint(&response(ip, 7447)).unwrap_err();
+            assert!(matches!(error, FeedError::UnusableIp { .. }), "{}", ip);
+        }
+    }
+
+    #[test]
+    fn out_of_range_port() {
+        for port in [0, 65536, 70000, u32::MAX] {
+            let error = feed_endpoint(&response("127.0.0.1", port)).unwrap_err();
+            assert!(
+                matches!(error, FeedError::InvalidPort { port: p } if p == port),
+                "{}",
+                port
+            );
+        }
+    }
+
+    #[test]
+    fn advertisement_ignores_timestamp() {
+        let first = AppResponse {
+            timestamp_ns: 1,
+            ..response("127.0.0.1", 7447)
+        };
+        let heartbeat = AppResponse {
+            timestamp_ns: 2,
+            ..response("127.0.0.1", 7447)
+        };
+        assert!(same_advertisement(&first, &heartbeat));
+        assert!(!same_advertisement(&first, &response("127.0.0.1", 7448)));
+    }
+
+    #[test]
+    fn serializes_kind_and_message() {
+        let error = feed_endpoint(&response("127.0.0.1", 65536)).unwrap_err();
+        assert_eq!(
+            serde_json::to_value(&error).unwrap(),
+            serde_json::json!({
+                "kind": "invalid_port",
+                "message": "App feed port 65536 is out of range, must be between 1 and 65535",
+            })
+        );
+    }
+}
@@ -0,0 +1,222 @@
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{HashMap, VecDeque};
+use std::mem;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::windows::Target;
+
+// Messages held for one event in a single frame before the oldest are dropped
+const MAX_PENDING_PER_EVENT: usize = 10_000;
+
+// How messages of an event are forwarded to the frontend
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ForwardPolicy {
+    // Emitted as soon as it arrives, with the message as payload
+    Immediate,
+    // Collected over a frame and emitted as an array of every message
+    Batch,
+    // Collected over a frame and emitted as an array holding only the latest message
+    Latest,
+}
+
+// Counters for one event, returned by the forwarder_metrics command
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct EventMetrics {
+    pub received: u64,
+    pub emitted: u64,
+    pub coalesced: u64,
+    pub dropped: u64,
+}
+
+type Emit = Box<dyn Fn(&'static str, &Target, Value) + Send + Sync>;
+
+#[derive(Default)]
+struct Frame {
+    // Events in the order they showed up in the frame so they are flushed in that order
+    events: Vec<(&'static str, Target, VecDeque<Value>)>,
+}
+
+impl Frame {
+    fn position(&self, event: &'static str, target: &Target) -> Option<usize> {
+        self.events
+            .iter()
+            .position(|(e, t, _)| *e == event && t == target)
+    }
+
+    fn pending(&mut self, event: &'static str, target: Target) -> &mut VecDeque<Value> {
+        let idx = match self.position(event, &target) {
+            Some(idx) => idx,
+            None => {
+                self.events.push((event, target, VecDeque::new()));
+                self.events.len() - 1
+            }
+        };
+
+        &mut self.events[idx].2
+    }
+
+    // Replace whatever is pending for the event with the message, which goes out after everything
+    // else that came in before it. Returns true if a message was replaced
+    fn latest(&mut self, event: &'static str, target: Target, payload: Value) -> bool {
+        let replaced = match self.position(event, &target) {
+            Some(idx) => {
+                self.events.remove(idx);
+                true
+            }
+            None => false,
+        };
+        self.events.push((event, target, VecDeque::from([payload])));
+
+        replaced
+    }
+}
+
+// Forwards app feed messages to the frontend without flooding the webview IPC during fast resims.
+// High rate events are coalesced per frame and sent as arrays, everything else goes out right
+// away after whatever is pending so the frontend still sees messages in order
+pub struct EventForwarder {
+    frame_interval: Duration,
+    emit: Emit,
+    frame: Mutex<Frame>,
+    metrics: Mutex<HashMap<&'static str, EventMetrics>>,
+}
+
+impl EventForwarder {
+    pub fn new<F>(frame_interval: Duration, emit: F) -> Self
+    where
+        F: Fn(&'static str, &Target, Value) + Send + Sync + 'static,
+    {
+        Self {
+            frame_interval,
+            emit: Box::new(emit),
+            frame: Mutex::new(Frame::default()),
+            metrics: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn forward(
+        &self,
+        event: &'static str,
+        policy: ForwardPolicy,
+        target: Target,
+        payload: Value,
+    ) {
+        // Held while emitting so flushes and immediate events can't overtake each other
+        let mut frame = self.frame.lock().unwrap();
+        let mut metrics = self.metrics.lock().unwrap();
+        let event_metrics = metrics.entry(event).or_default();
+        event_metrics.received += 1;
+
+        match policy {
+            ForwardPolicy::Immediate => {
+                event_metrics.emitted += 1;
+                drop(metrics);
+
+                self.flush_frame(&mut frame);
+                (self.emit)(event, &target, payload);
+            }
+            ForwardPolicy::Batch => {
+                let pending = frame.pending(event, target);
+                if pending.len() >= MAX_PENDING_PER_EVENT {
+                    pending.pop_front();
+                    event_metrics.dropped += 1;
+                }
+                pending.push_back(payload);
+            }
+            ForwardPolicy::Latest => {
+                if frame.latest(event, target, payload) {
+                    event_metrics.coalesced += 1;
+                }
+            }
+        }
+    }
+
+    // Emit everything collected in the current frame
+    pub fn flush(&self) {
+        let mut frame = self.frame.lock().unwrap();
+        self.flush_frame(&mut frame);
+    }
+
+    fn flush_frame(&self, frame: &mut Frame) {
+        let events = mem::take(&mut frame.events);
+        if events.is_empty() {
+            return;
+        }
+
+        {
+            let mut metrics = self.metrics.lock().unwrap();
+            for (event, _, _) in &events {
+                metrics.entry(event).or_default().emitted += 1;
+            }
+        }
+
+        for (event, target, payloads) in events {
+            (self.emit)(event, &target, Value::Array(payloads.into()));
+        }
+    }
+
+    pub fn metrics(&self) -> HashMap<&'static str, EventMetrics> {
+        self.metrics.lock().unwrap().clone()
+    }
+
+    // Flush pending messages every frame interval
+    pub async fn run(self: Arc<Self>) {
+        let mut interval = tokio::time::interval(self.frame_interval);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+        loop {
+            interval.tick().await;
+            self.flush();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    type Emitted = Arc<Mutex<Vec<(&'static str, Value)>>>;
+
+    fn forwarder() -> (EventForwarder, Emitted) {
+        let emitted = Arc::new(Mutex::new(Vec::new()));
+        let sink = emitted.clone();
+        let forwarder = EventForwarder::new(Duration::from_millis(16), move |event, _, payload| {
+            sink.lock().unwrap().push((event, payload))
+        });
+
+        (forwarder, emitted)
+    }
+
+    #[test]
+    fn latest_goes_out_after_what_came_before_it() {
+        let (forwarder, emitted) = forwarder();
+        forwarder.forward("pnl", ForwardPolicy::Latest, Target::All, json!(1));
+        forwarder.forward("candle", ForwardPolicy::Batch, Target::All, json!("a"));
+        forwarder.forward("pnl", ForwardPolicy::Latest, Target::All, json!(2));
+        forwarder.flush();
+
+        assert_eq!(
+            *emitted.lock().unwrap(),
+            vec![("candle", json!(["a"])), ("pnl", json!([2]))]
+        );
+        assert_eq!(forwarder.metrics()["pnl"].coalesced, 1);
+    }
+
+    #[test]
+    fn batch_drops_the_oldest_when_full() {
+        let (forwarder, emitted) = forwarder();
+        for n in 0..=MAX_PENDING_PER_EVENT {
+            forwarder.forward("candle", ForwardPolicy::Batch, Target::All, json!(n));
+        }
+        forwarder.flush();
+
+        let emitted = emitted.lock().unwrap();
+        let payloads = emitted[0].1.as_array().unwrap();
+        assert_eq!(payloads.len(), MAX_PENDING_PER_EVENT);
+        assert_eq!(payloads[0], json!(1));
+        assert_eq!(forwarder.metrics()["candle"].dropped, 1);
+    }
+}
@@ -0,0 +1,186 @@
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tradebot_protos::messages::enums::MessageType;
+
+use crate::sink::EventSink;
+use crate::supervisor::ServiceLink;
+
+// A service is considered down when nothing was heard from it for this many heartbeat intervals
+const MISSED_HEARTBEATS: u32 = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BackendService {
+    App,
+    Datafeed,
+    Algo,
+    Broker,
+}
+
+impl BackendService {
+    pub const ALL: [BackendService; 4] = [
+        BackendService::App,
+        BackendService::Datafeed,
+        BackendService::Algo,
+        BackendService::Broker,
+    ];
+
+    // Service that produces messages of the given type, if it is obvious from the type
+    pub fn from_mtype(mtype: MessageType) -> Option<BackendService> {
+        match mtype {
+            MessageType::Chart | MessageType::Candle => Some(BackendService::Datafeed),
+            MessageType::AlgoChart
+            | MessageType::Point
+            | MessageType::Advice
+            | MessageType::Rectangle => Some(BackendService::Algo),
+            MessageType::Order | MessageType::OrderFilled | MessageType::OrderList => {
+                Some(BackendService::Broker)
+            }
+            _ => None,
+        }
+    }
+}
+
+// Sent to the frontend as part of the service_health event
+#[derive(Debug, Clone, Serialize)]
+pub struct ServiceHealth {
+    pub service: BackendService,
+    pub alive: bool,
+    pub last_seen_ms: Option<u64>,
+    pub latency_ms: Option<u64>,
+}
+
+struct LastSeen {
+    at: Instant,
+    timestamp_ms: u64,
+}
+
+// Tracks when each backend service was last heard from. The app service is pinged with an
+// AppRequest every heartbeat interval and the round trip to its AppResponse is recorded as latency.
+// Datafeed, algo and broker services are seen through the messages they publish on the app feed
+pub struct HealthMonitor {
+    interval: Duration,
+    last_seen: Mutex<HashMap<BackendService, LastSeen>>,
+    latency: Mutex<Option<Duration>>,
+    ping_sent: Mutex<Option<Instant>>,
+    app_request_pending: AtomicBool,
+}
+
+impl HealthMonitor {
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            last_seen: Mutex::new(HashMap::new()),
+            latency: Mutex::new(None),
+            ping_sent: Mutex::new(None),
+            app_request_pending: AtomicBool::new(false),
+        }
+    }
+
+    pub fn seen(&self, service: BackendService) {
+        let timestamp_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64;
+
+        self.last_seen.lock().unwrap().insert(
+            service,
+            LastSeen {
+                at: Instant::now(),
+                timestamp_ms,
+            },
+        );
+    }
+
+    pub fn seen_mtype(&self, mtype: MessageType) {
+        if let Some(service) = BackendService::from_mtype(mtype) {
+            self.seen(service);
+        }
+    }
+
+    // Frontend asked for the app response, so the next one has to be passed on even if it is
+    // the same as the last one
+    pub fn app_requested(&self) {
+        self.app_request_pending.store(true, Ordering::SeqCst);
+    }
+
+    // Record an AppResponse from the app service. Returns true if the frontend asked for it
+    pub fn app_response(&self) -> bool {
+        self.seen(BackendService::App);
+
+        if let Some(sent) = self.ping_sent.lock().unwrap().take() {
+            *self.latency.lock().unwrap() = Some(sent.elapsed());
+        }
+
+        self.app_request_pending.swap(false, Ordering::SeqCst)
+    }
+
+    // A new connection is being built, pings sent on the old one won't be answered
+    pub fn reconnecting(&self) {
+        *self.ping_sent.lock().unwrap() = None;
+    }
+
+    fn alive(&self, seen: Option<&LastSeen>) -> bool {
+        seen.is_some_and(|seen| seen.at.elapsed() < self.interval * MISSED_HEARTBEATS)
+    }
+
+    fn is_alive(&self, service: BackendService) -> bool {
+        self.alive(self.last_seen.lock().unwrap().get(&service))
+    }
+
+    pub fn snapshot(&self) -> Vec<ServiceHealth> {
+        let last_seen = self.last_seen.lock().unwrap();
+        let latency = *self.latency.lock().unwrap();
+
+        BackendService::ALL
+            .into_iter()
+            .map(|service| {
+                let seen = last_seen.get(&service);
+                ServiceHealth {
+                    service,
+                    alive: self.alive(seen),
+                    last_seen_ms: seen.map(|seen| seen.timestamp_ms),
+                    latency_ms: (service == BackendService::App)
+                        .then_some(latency)
+                        .flatten()
+                        .map(|latency| latency.as_millis() as u64),
+                }
+            })
+            .collect()
+    }
+
+    // Ping the app service every heartbeat interval and let the frontend know how services are doing
+    pub async fn run(self: Arc<Self>, service_link: Arc<ServiceLink>, sink: Arc<dyn EventSink>) {
+        let mut interval = tokio::time::interval(self.interval);
+
+        loop {
+            interval.tick().await;
+
+            if let Ok(connection) = service_link.connection().await {
+                if self.is_alive(BackendService::App) {
+                    service_link.set_healthy();
+                } else if self.ping_sent.lock().unwrap().is_some() {
+                    service_link.set_degraded("No response from the TradeBot app service");
+                }
+
+                // Measure from the first unanswered ping so a slow service isn't reported as fast
+                self.ping_sent
+                    .lock()
+                    .unwrap()
+                    .get_or_insert_with(Instant::now);
+
+                let result = connection
+                    .publishers
+                    .app_request
+                    .publish(service_link.app_request())
+                    .await;
+                let _ = service_link.report("heartbeat", result);
+            }
+
+            sink.emit_all("service_health", self.snapshot());
+        }
+    }
+}
@@ -0,0 +1,60 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use uuid::Uuid;
+
+use crate::config::ServiceConfig;
+
+// File in the app data dir holding the client id of this install
+const CLIENT_ID_FILE: &str = "client_id";
+
+// Send time stamped on every outgoing request
+pub fn timestamp_ns() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos() as u64
+}
+
+// Id sent as the user id on app requests so backend logs can tell app instances apart. Uses the
+// client-id from service config if set, otherwise the id stored in the app data dir, generating
+// and storing a new one on first run
+pub fn client_id(service_config: &ServiceConfig, data_dir: Option<PathBuf>) -> String {
+    if let Some(client_id) = service_config
+        .client_id
+        .as_ref()
+        .filter(|client_id| !client_id.trim().is_empty())
+    {
+        return client_id.trim().to_owned();
+    }
+
+    let Some(path) = data_dir.map(|dir| dir.join(CLIENT_ID_FILE)) else {
+        eprintln!("No app data dir, client id will change on every run");
+        return Uuid::new_v4().to_string();
+    };
+
+    if let Ok(client_id) = fs::read_to_string(&path) {
+        if !client_id.trim().is_empty() {
+            return client_id.trim().to_owned();
+        }
+    }
+
+    let client_id = Uuid::new_v4().to_string();
+    if let Err(e) = store(&path, &client_id) {
+        eprintln!(
+            "Failed to store client id in {}, it will change on every run: {}",
+            path.display(),
+            e
+        );
+    }
+
+    client_id
+}
+
+fn store(path: &Path, client_id: &str) -> std::io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    fs::write(path, client_id)
+}
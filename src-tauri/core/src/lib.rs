@@ -0,0 +1,25 @@
+// Everything the TradeBot app does short of drawing it: the connection to TradeBot services, app feed
+// subscriptions and routing, recording and replay. Frontends drive it through AppCore and get its
+// events through an EventSink
+pub mod app;
+pub mod config;
+pub mod error;
+pub mod feeds;
+pub mod forwarder;
+pub mod health;
+pub mod identity;
+#[cfg(feature = "mock-backend")]
+pub mod mock;
+pub mod recorder;
+pub mod replay;
+pub mod requests;
+pub mod service;
+pub mod session;
+pub mod sink;
+pub mod subscriptions;
+pub mod supervisor;
+pub mod topics;
+pub mod windows;
+
+pub use app::AppCore;
+pub use sink::EventSink;
@@ -0,0 +1,263 @@
+use async_trait::async_trait;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use tradebot_protos::messages::enums::MessageType;
+use tradebot_protos::messages::{
+    AppRequest, AppResponse, Batch, Candle, Chart, ChartRequest, Network, Order, OverallRequest,
+    OverallStats, ReadFromDirRequest, ReadFromDirResponse, RunYaml, TopicMtype,
+};
+use zenoh_node::builder::NodeBuilder;
+use zenoh_node::error::NodeError;
+use zenoh_node::node::{Abort, Node, Publisher, Subscribe, SubscriberError};
+
+use crate::config::ServiceConfig;
+use crate::identity;
+use crate::topics::Topic;
+
+// App feed topics the mock backend advertises and publishes on
+const CHART_TOPIC: &str = "mock/chart";
+const CANDLE_TOPIC: &str = "mock/candle";
+const ORDER_TOPIC: &str = "mock/order";
+const OVERALL_STATS_TOPIC: &str = "mock/overall_stats";
+
+// What the mock backend answers with. Timestamps are stamped when the message is sent
+#[derive(Debug, Clone, Default)]
+pub struct MockScript {
+    // Batches advertised in every app response
+    pub batches: Vec<Batch>,
+    // Batches a run of any yaml adds to the advertised ones
+    pub run_batches: Vec<Batch>,
+    // Batches read from any log dir
+    pub log_batches: Vec<Batch>,
+    // Answer to chart requests, followed by the candles and orders
+    pub chart: Chart,
+    pub candles: Vec<Candle>,
+    pub orders: Vec<Order>,
+    // Answer to overall requests
+    pub overall_stats: OverallStats,
+}
+
+// A request the mock backend received
+#[derive(Debug, Clone, PartialEq)]
+pub enum Received {
+    App(AppRequest),
+    ReadFromDir(ReadFromDirRequest),
+    Chart(ChartRequest),
+    Overall(OverallRequest),
+    RunYaml(RunYaml),
+}
+
+struct MockPublishers {
+    app_response: Publisher<AppResponse>,
+    read_from_dir_response: Publisher<ReadFromDirResponse>,
+    chart: Publisher<Chart>,
+    candle: Publisher<Candle>,
+    order: Publisher<Order>,
+    overall_stats: Publisher<OverallStats>,
+}
+
+struct MockResponder {
+    publishers: Arc<MockPublishers>,
+    script: MockScript,
+    // Batches advertised so far, runs add to them
+    batches: Vec<Batch>,
+    feed: SocketAddr,
+    received: Arc<Mutex<Vec<Received>>>,
+}
+
+impl MockResponder {
+    fn app_response(&self) -> AppResponse {
+        let topic = |mtype: MessageType, topic: &str| TopicMtype {
+            mtype: mtype as i32,
+            topic: topic.to_owned(),
+        };
+
+        AppResponse {
+            timestamp_ns: identity::timestamp_ns(),
+            network: Some(Network {
+                ip: self.feed.ip().to_string(),
+                port: self.feed.port() as u32,
+            }),
+            topics: vec![
+                topic(MessageType::Chart, CHART_TOPIC),
+                topic(MessageType::Candle, CANDLE_TOPIC),
+                topic(MessageType::Order, ORDER_TOPIC),
+                topic(MessageType::OverallStats, OVERALL_STATS_TOPIC),
+            ],
+            batches: self.batches.clone(),
+        }
+    }
+}
+
+#[async_trait]
+impl Subscribe<AppRequest> for MockResponder {
+    async fn on_data(&mut self, msg: AppRequest) -> Result<(), SubscriberError> {
+        self.received.lock().unwrap().push(Received::App(msg));
+        publish(&self.publishers.app_response, self.app_response()).await;
+        Ok(())
+    }
+}
+
+// Runs advertise their batches on the same app feed, like a backtest started next to a live run
+#[async_trait]
+impl Subscribe<RunYaml> for MockResponder {
+    async fn on_data(&mut self, msg: RunYaml) -> Result<(), SubscriberError> {
+        self.received.lock().unwrap().push(Received::RunYaml(msg));
+        self.batches.extend(self.script.run_batches.iter().cloned());
+        publish(&self.publishers.app_response, self.app_response()).await;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Subscribe<ReadFromDirRequest> for MockResponder {
+    async fn on_data(&mut self, msg: ReadFromDirRequest) -> Result<(), SubscriberError> {
+        self.received
+            .lock()
+            .unwrap()
+            .push(Received::ReadFromDir(msg));
+
+        let response = ReadFromDirResponse {
+            timestamp_ns: identity::timestamp_ns(),
+            batches: self.script.log_batches.clone(),
+        };
+        publish(&self.publishers.read_from_dir_response, response).await;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Subscribe<ChartRequest> for MockResponder {
+    async fn on_data(&mut self, msg: ChartRequest) -> Result<(), SubscriberError> {
+        self.received.lock().unwrap().push(Received::Chart(msg));
+
+        let mut chart = self.script.chart.clone();
+        chart.timestamp_ns = identity::timestamp_ns();
+        publish(&self.publishers.chart, chart).await;
+
+        for candle in &self.script.candles {
+            publish(&self.publishers.candle, candle.clone()).await;
+        }
+        for order in &self.script.orders {
+            publish(&self.publishers.order, order.clone()).await;
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Subscribe<OverallRequest> for MockResponder {
+    async fn on_data(&mut self, msg: OverallRequest) -> Result<(), SubscriberError> {
+        self.received.lock().unwrap().push(Received::Overall(msg));
+
+        let mut overall_stats = self.script.overall_stats.clone();
+        overall_stats.timestamp_ns = identity::timestamp_ns();
+        publish(&self.publishers.overall_stats, overall_stats).await;
+        Ok(())
+    }
+}
+
+async fn publish<T: prost::Message>(publisher: &Publisher<T>, msg: T) {
+    if let Err(e) = publisher.publish(msg).await {
+        eprintln!("Mock backend failed to publish: {}", e);
+    }
+}
+
+// Stand-in for TradeBot services in a single process, for tests. Listens for requests on the
+// service topics of the service config and answers them from a script, with the app feed on its
+// own node at the feed endpoint
+pub struct MockBackend {
+    _service_node: Node,
+    _feed_node: Node,
+    subscriber: Box<dyn Abort>,
+    publishers: Arc<MockPublishers>,
+    received: Arc<Mutex<Vec<Received>>>,
+}
+
+impl MockBackend {
+    pub async fn start(
+        service_config: &ServiceConfig,
+        feed: SocketAddr,
+        script: MockScript,
+    ) -> Result<Self, NodeError> {
+        let mut builder = NodeBuilder::new();
+        if let Some(config) = &service_config.zenoh_config_path {
+            builder.set_config_path(config);
+        }
+        builder.set_network((service_config.ip.clone(), service_config.port));
+        let service_node = builder.build().await?;
+
+        let mut builder = NodeBuilder::new();
+        builder.set_network((feed.ip().to_string(), feed.port()));
+        let feed_node = builder.build().await?;
+
+        let topics = service_config.topic_config();
+        let publishers = Arc::new(MockPublishers {
+            app_response: service_node
+                .new_publisher(topics.get(Topic::AppResponse))
+                .await?,
+            read_from_dir_response: service_node
+                .new_publisher(topics.get(Topic::ReadFromDirResponse))
+                .await?,
+            chart: feed_node.new_publisher(CHART_TOPIC).await?,
+            candle: feed_node.new_publisher(CANDLE_TOPIC).await?,
+            order: feed_node.new_publisher(ORDER_TOPIC).await?,
+            overall_stats: feed_node.new_publisher(OVERALL_STATS_TOPIC).await?,
+        });
+
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let responder = MockResponder {
+            publishers: publishers.clone(),
+            batches: script.batches.clone(),
+            script,
+            feed,
+            received: received.clone(),
+        };
+
+        let mut subscriber = service_node.new_subscriber(responder).await?;
+        service_node
+            .subscribe::<AppRequest>(topics.get(Topic::AppRequest), &mut subscriber)
+            .await?;
+        service_node
+            .subscribe::<ReadFromDirRequest>(topics.get(Topic::ReadFromDirRequest), &mut subscriber)
+            .await?;
+        service_node
+            .subscribe::<ChartRequest>(topics.get(Topic::ChartRequest), &mut subscriber)
+            .await?;
+        service_node
+            .subscribe::<OverallRequest>(topics.get(Topic::OverallRequest), &mut subscriber)
+            .await?;
+        service_node
+            .subscribe::<RunYaml>(topics.get(Topic::RunYaml), &mut subscriber)
+            .await?;
+
+        Ok(Self {
+            _service_node: service_node,
+            _feed_node: feed_node,
+            subscriber: Box::new(subscriber),
+            publishers,
+            received,
+        })
+    }
+
+    // Every request received so far, oldest first
+    pub fn received(&self) -> Vec<Received> {
+        self.received.lock().unwrap().clone()
+    }
+
+    // Publish synthetic candles and orders on the app feed outside of a chart request
+    pub async fn stream(&self, candles: Vec<Candle>, orders: Vec<Order>) {
+        for candle in candles {
+            publish(&self.publishers.candle, candle).await;
+        }
+        for order in orders {
+            publish(&self.publishers.order, order).await;
+        }
+    }
+}
+
+impl Drop for MockBackend {
+    fn drop(&mut self) {
+        self.subscriber.abort();
+    }
+}
@@ -0,0 +1,322 @@
+use prost::Message;
+use serde::Serialize;
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, ErrorKind, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tradebot_protos::messages::enums::MessageType;
+use tradebot_protos::messages::{AppResponse, ChartRequest, OverallRequest};
+
+use crate::identity;
+use crate::windows::WindowScope;
+
+// Start of every recording so replay can tell it apart from other files
+pub const MAGIC: &[u8; 8] = b"TBREC001";
+pub const EXTENSION: &str = "tbrec";
+
+// Records with negative message types carry app context instead of app feed messages. Replay needs
+// them to show the strategy list and to tell which strategy and batch replayed messages are for
+// Strategy list, an AppResponse holding the batches of every feed
+pub const STRATEGY_LIST_RECORD: i32 = -1;
+// What app feed strategy messages are for from here on, a ChartRequest with an empty strategy id
+// if nothing is streamed
+pub const STREAMING_RECORD: i32 = -2;
+// Batch overall messages are for from here on, an OverallRequest
+pub const OVERALL_RECORD: i32 = -3;
+
+// One app feed message as it was received. On disk the receive time (u64), message type (i32) and
+// length (u32), all little endian, followed by the protobuf encoded message
+pub struct Record {
+    pub timestamp_ns: u64,
+    pub mtype: i32,
+    pub bytes: Vec<u8>,
+}
+
+impl Record {
+    pub fn write_to(&self, writer: &mut impl Write) -> io::Result<()> {
+        writer.write_all(&self.timestamp_ns.to_le_bytes())?;
+        writer.write_all(&self.mtype.to_le_bytes())?;
+        writer.write_all(&(self.bytes.len() as u32).to_le_bytes())?;
+        writer.write_all(&self.bytes)
+    }
+
+    // Next record, None at the end of the recording. A record cut short, like the last one of a
+    // recording the app crashed during, also counts as the end
+    pub fn read_from(reader: &mut impl Read) -> io::Result<Option<Self>> {
+        let mut header = [0u8; 16];
+        match reader.read_exact(&mut header) {
+            Ok(()) => (),
+            Err(e) if e.kind() == ErrorKind::UnexpectedEof => return Ok(None),
+            Err(e) => return Err(e),
+        }
+
+        let timestamp_ns = u64::from_le_bytes(header[0..8].try_into().unwrap());
+        let mtype = i32::from_le_bytes(header[8..12].try_into().unwrap());
+        let len = u32::from_le_bytes(header[12..16].try_into().unwrap());
+
+        let mut bytes = vec![0u8; len as usize];
+        match reader.read_exact(&mut bytes) {
+            Ok(()) => (),
+            Err(e) if e.kind() == ErrorKind::UnexpectedEof => return Ok(None),
+            Err(e) => return Err(e),
+        }
+
+        Ok(Some(Self {
+            timestamp_ns,
+            mtype,
+            bytes,
+        }))
+    }
+}
+
+// Every record of a recording
+pub fn read_recording(path: &Path) -> io::Result<Vec<Record>> {
+    let mut reader = BufReader::new(File::open(path)?);
+
+    let mut magic = [0u8; 8];
+    reader.read_exact(&mut magic)?;
+    if &magic != MAGIC {
+        return Err(io::Error::new(
+            ErrorKind::InvalidData,
+            format!("{} is not an app feed recording", path.display()),
+        ));
+    }
+
+    let mut records = Vec::new();
+    while let Some(record) = Record::read_from(&mut reader)? {
+        records.push(record);
+    }
+
+    Ok(records)
+}
+
+// Scope a streaming record is for, None if nothing was streamed
+pub fn streaming_scope(request: ChartRequest) -> Option<WindowScope> {
+    if request.strategy_id.is_empty() {
+        return None;
+    }
+
+    Some(WindowScope {
+        batch_id: request.batch_id,
+        strategy_id: request.strategy_id,
+        symbol: request.symbol,
+        period_s: request.period_s,
+    })
+}
+
+// Sent to the frontend as the recording event and returned by the recording commands
+#[derive(Debug, Clone, Serialize)]
+pub struct RecordingStatus {
+    pub path: String,
+    pub records: u64,
+    pub started_ns: u64,
+}
+
+struct Recording {
+    status: RecordingStatus,
+    writer: BufWriter<File>,
+    // Scope of the last streaming record written. Every message is preceded by the scope streamed on
+    // its feed when it differs
+    streaming: Option<WindowScope>,
+}
+
+impl Recording {
+    fn write(&mut self, mtype: i32, bytes: Vec<u8>) -> io::Result<()> {
+        let record = Record {
+            timestamp_ns: identity::timestamp_ns(),
+            mtype,
+            bytes,
+        };
+        record.write_to(&mut self.writer)?;
+        self.status.records += 1;
+
+        Ok(())
+    }
+
+    fn write_streaming(&mut self, streaming: Option<WindowScope>) -> io::Result<()> {
+        let request = match &streaming {
+            Some(scope) => ChartRequest {
+                timestamp_ns: 0,
+                batch_id: scope.batch_id.clone(),
+                strategy_id: scope.strategy_id.clone(),
+                symbol: scope.symbol.clone(),
+                period_s: scope.period_s,
+            },
+            None => ChartRequest::default(),
+        };
+        self.write(STREAMING_RECORD, request.encode_to_vec())?;
+        self.streaming = streaming;
+
+        Ok(())
+    }
+
+    fn write_overall(&mut self, batch_id: &str) -> io::Result<()> {
+        let request = OverallRequest {
+            timestamp_ns: 0,
+            batch_id: batch_id.to_owned(),
+        };
+        self.write(OVERALL_RECORD, request.encode_to_vec())
+    }
+}
+
+// Writes every app feed message to a session file while recording so the session can be replayed
+// later exactly as it was seen
+pub struct Recorder {
+    dir: Option<PathBuf>,
+    recording: Mutex<Option<Recording>>,
+    // Batch of the last overall request, written at the start of every recording
+    overall_batch: Mutex<Option<String>>,
+}
+
+impl Recorder {
+    pub fn new(dir: Option<PathBuf>) -> Self {
+        Self {
+            dir,
+            recording: Mutex::new(None),
+            overall_batch: Mutex::new(None),
+        }
+    }
+
+    // Start a new session file in the recording dir, beginning with the current strategy list.
+    // Keeps the current one if already recording
+    pub fn start(&self, strategy_list: &AppResponse) -> io::Result<RecordingStatus> {
+        let mut recording = self.recording.lock().unwrap();
+        if let Some(recording) = &*recording {
+            return Ok(recording.status.clone());
+        }
+
+        let Some(dir) = &self.dir else {
+            return Err(io::Error::new(
+                ErrorKind::NotFound,
+                "no app data dir to write recordings to, set recording-dir in the service config",
+            ));
+        };
+
+        fs::create_dir_all(dir)?;
+        let started_ns = identity::timestamp_ns();
+        let path = dir.join(format!("session-{}.{}", started_ns, EXTENSION));
+
+        let mut writer = BufWriter::new(File::create(&path)?);
+        writer.write_all(MAGIC)?;
+
+        let status = RecordingStatus {
+            path: path.display().to_string(),
+            records: 0,
+            started_ns,
+        };
+        let mut new_recording = Recording {
+            status,
+            writer,
+            streaming: None,
+        };
+        new_recording.write(STRATEGY_LIST_RECORD, strategy_list.encode_to_vec())?;
+        new_recording.write_streaming(None)?;
+        if let Some(batch_id) = &*self.overall_batch.lock().unwrap() {
+            new_recording.write_overall(batch_id)?;
+        }
+
+        let status = new_recording.status.clone();
+        *recording = Some(new_recording);
+
+        Ok(status)
+    }
+
+    // Finish the session file. Returns None if nothing was being recorded
+    pub fn stop(&self) -> io::Result<Option<RecordingStatus>> {
+        let Some(mut recording) = self.recording.lock().unwrap().take() else {
+            return Ok(None);
+        };
+
+        recording.writer.flush()?;
+        Ok(Some(recording.status))
+    }
+
+    pub fn status(&self) -> Option<RecordingStatus> {
+        self.recording
+            .lock()
+            .unwrap()
+            .as_ref()
+            .map(|recording| recording.status.clone())
+    }
+
+    // Write a received message if recording, preceded by a streaming record if what is streamed
+    // changed since the last message
+    pub fn record<T: Message>(&self, mtype: MessageType, msg: &T, streaming: Option<WindowScope>) {
+        self.write_with(|recording| {
+            if recording.streaming != streaming {
+                recording.write_streaming(streaming)?;
+            }
+            recording.write(mtype as i32, msg.encode_to_vec())
+        });
+    }
+
+    // Overall messages received from now on are for the batch, written if recording
+    pub fn record_overall_batch(&self, batch_id: &str) {
+        *self.overall_batch.lock().unwrap() = Some(batch_id.to_owned());
+        self.write_with(|recording| recording.write_overall(batch_id));
+    }
+
+    // Write the strategy list if recording, after feeds were added or removed
+    pub fn record_strategy_list(&self, strategy_list: &AppResponse) {
+        self.write_with(|recording| {
+            recording.write(STRATEGY_LIST_RECORD, strategy_list.encode_to_vec())
+        });
+    }
+
+    // A failed write ends the recording
+    fn write_with(&self, write: impl FnOnce(&mut Recording) -> io::Result<()>) {
+        let mut guard = self.recording.lock().unwrap();
+        let Some(recording) = guard.as_mut() else {
+            return;
+        };
+
+        if let Err(e) = write(recording) {
+            eprintln!(
+                "Failed to write to recording {}, stopping it: {}",
+                recording.status.path, e
+            );
+            *guard = None;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cut_short_record_ends_the_recording() {
+        let record = Record {
+            timestamp_ns: 1,
+            mtype: MessageType::Candle as i32,
+            bytes: vec![1, 2, 3, 4],
+        };
+        let mut written = Vec::new();
+        record.write_to(&mut written).unwrap();
+        record.write_to(&mut written).unwrap();
+
+        for len in [written.len() - 2, written.len() - 10] {
+            let mut reader = &written[..len];
+            let first = Record::read_from(&mut reader).unwrap().unwrap();
+            assert_eq!(first.bytes, record.bytes);
+            assert!(Record::read_from(&mut reader).unwrap().is_none(), "{}", len);
+        }
+    }
+
+    #[test]
+    fn recording_starts_with_the_overall_batch() {
+        let dir =
+            std::env::temp_dir().join(format!("tradebot-app-recorder-{}", std::process::id()));
+        let recorder = Recorder::new(Some(dir.clone()));
+        recorder.record_overall_batch("live");
+
+        let status = recorder.start(&AppResponse::default()).unwrap();
+        recorder.stop().unwrap();
+        let records = read_recording(Path::new(&status.path)).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        let overall = records.iter().find(|r| r.mtype == OVERALL_RECORD).unwrap();
+        let request = OverallRequest::decode(overall.bytes.as_slice()).unwrap();
+        assert_eq!(request.batch_id, "live");
+    }
+}
@@ -0,0 +1,331 @@
+use prost::Message;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::io;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::Notify;
+use tradebot_protos::messages::enums::MessageType;
+use tradebot_protos::messages::{AppResponse, ChartRequest, OverallRequest};
+
+use crate::app::AppCore;
+use crate::recorder::{self, Record, OVERALL_RECORD, STRATEGY_LIST_RECORD, STREAMING_RECORD};
+use crate::session::SessionStore;
+use crate::windows::WindowScope;
+
+// Sent to the frontend as the replay event and returned by the replay commands
+#[derive(Debug, Clone, Serialize)]
+pub struct ReplayStatus {
+    pub path: String,
+    pub records: usize,
+    pub position: usize,
+    // Recording time of the current position and of the whole recording, from its first record
+    pub position_ns: u64,
+    pub duration_ns: u64,
+    pub playing: bool,
+    pub speed: f64,
+}
+
+struct Loaded {
+    path: String,
+    records: Arc<Vec<Record>>,
+    position: usize,
+    // App feed messages before this are skipped after a seek, context records are still replayed
+    skip_until: usize,
+    // Records before this are replayed without waiting to catch up after a seek
+    catch_up_until: usize,
+    playing: bool,
+    speed: f64,
+}
+
+impl Loaded {
+    fn status(&self) -> ReplayStatus {
+        let start = self.records.first().map_or(0, |r| r.timestamp_ns);
+        let end = self.records.last().map_or(0, |r| r.timestamp_ns);
+        let at = self
+            .records
+            .get(self.position.max(self.catch_up_until))
+            .map_or(end, |r| r.timestamp_ns);
+
+        ReplayStatus {
+            path: self.path.clone(),
+            records: self.records.len(),
+            position: self.position,
+            position_ns: at.saturating_sub(start),
+            duration_ns: end.saturating_sub(start),
+            playing: self.playing,
+            speed: self.speed,
+        }
+    }
+
+    // Charts start over with every Chart message, so replay from the last one before the position
+    // and catch up from there instead of replaying the whole recording
+    fn seek(&mut self, position_ns: u64) {
+        let start = self.records.first().map_or(0, |r| r.timestamp_ns);
+        let target = self
+            .records
+            .partition_point(|r| r.timestamp_ns.saturating_sub(start) < position_ns);
+        let chart = self.records[..target]
+            .iter()
+            .rposition(|r| r.mtype == MessageType::Chart as i32)
+            .unwrap_or(0);
+
+        self.position = 0;
+        self.skip_until = chart;
+        self.catch_up_until = target;
+    }
+}
+
+#[derive(Default)]
+struct State {
+    loaded: Option<Loaded>,
+    // What the replayed session was streaming, replayed strategy messages are routed by it instead of
+    // by what live feeds stream
+    streaming: Option<WindowScope>,
+    // Bumped on every control so a record waited for before it isn't replayed after it
+    generation: u64,
+}
+
+// Replays a recorded session to the frontend as the same events it got live, without TradeBot
+// services running. Replayed messages only go through the registry and the forwarder, live feed
+// health, outstanding requests, recording and the live session store never see them
+#[derive(Default)]
+pub struct ReplayPlayer {
+    state: Mutex<State>,
+    changed: Notify,
+    // What was replayed so far, for views that mount after the replayed chart came in
+    session: SessionStore,
+}
+
+impl ReplayPlayer {
+    pub fn new() -> Self {
+        Self {
+            state: Mutex::new(State::default()),
+            changed: Notify::new(),
+            session: SessionStore::new(),
+        }
+    }
+
+    // Load a recording, paused at its start
+    pub fn open(&self, path: &Path) -> io::Result<ReplayStatus> {
+        let records = recorder::read_recording(path)?;
+        let loaded = Loaded {
+            path: path.display().to_string(),
+            records: Arc::new(records),
+            position: 0,
+            skip_until: 0,
+            catch_up_until: 0,
+            playing: false,
+            speed: 1.0,
+        };
+        let status = loaded.status();
+
+        let mut state = self.state.lock().unwrap();
+        state.loaded = Some(loaded);
+        state.streaming = None;
+        state.generation += 1;
+        drop(state);
+
+        self.session.clear();
+        self.changed.notify_waiters();
+        Ok(status)
+    }
+
+    // Play from the current position, or from the start if the replay is over
+    pub fn play(&self) -> Option<ReplayStatus> {
+        self.control(|loaded| {
+            if loaded.position >= loaded.records.len() {
+                loaded.seek(0);
+                self.session.clear();
+            }
+            loaded.playing = true;
+        })
+    }
+
+    pub fn pause(&self) -> Option<ReplayStatus> {
+        self.control(|loaded| loaded.playing = false)
+    }
+
+    // What was replayed after the position is gone from the replay session, everything before it is
+    // caught up on again
+    pub fn seek(&self, position_ns: u64) -> Option<ReplayStatus> {
+        self.control(|loaded| {
+            loaded.seek(position_ns);
+            self.session.clear();
+        })
+    }
+
+    pub fn speed(&self, speed: f64) -> Option<ReplayStatus> {
+        self.control(|loaded| loaded.speed = speed)
+    }
+
+    // Unload the recording. Returns its last status, None if no recording was loaded
+    pub fn close(&self) -> Option<ReplayStatus> {
+        let mut state = self.state.lock().unwrap();
+        let loaded = state.loaded.take()?;
+        state.streaming = None;
+        state.generation += 1;
+        drop(state);
+
+        self.session.clear();
+        self.changed.notify_waiters();
+        Some(
+            Loaded {
+                playing: false,
+                ..loaded
+            }
+            .status(),
+        )
+    }
+
+    pub fn status(&self) -> Option<ReplayStatus> {
+        self.state
+            .lock()
+            .unwrap()
+            .loaded
+            .as_ref()
+            .map(Loaded::status)
+    }
+
+    // Everything replayed so far for the batch and, if given, the strategy. None if no recording is
+    // loaded
+    pub fn snapshot(
+        &self,
+        batch_id: &str,
+        scope: Option<&WindowScope>,
+    ) -> Option<BTreeMap<&'static str, Value>> {
+        self.state.lock().unwrap().loaded.as_ref()?;
+        Some(self.session.snapshot(batch_id, scope))
+    }
+
+    // Returns None if no recording is loaded
+    fn control(&self, control: impl FnOnce(&mut Loaded)) -> Option<ReplayStatus> {
+        let mut state = self.state.lock().unwrap();
+        let loaded = state.loaded.as_mut()?;
+        control(loaded);
+        let status = loaded.status();
+        state.generation += 1;
+        drop(state);
+
+        self.changed.notify_waiters();
+        Some(status)
+    }
+
+    // How long to wait before replaying the next record, None if there is nothing to replay
+    fn next(&self) -> Option<(Duration, u64)> {
+        let mut state = self.state.lock().unwrap();
+        let generation = state.generation;
+        let loaded = state.loaded.as_mut()?;
+
+        while loaded.position < loaded.skip_until && loaded.records[loaded.position].mtype >= 0 {
+            loaded.position += 1;
+        }
+        if !loaded.playing || loaded.position >= loaded.records.len() {
+            return None;
+        }
+
+        let delay = if loaded.position == 0 || loaded.position < loaded.catch_up_until {
+            Duration::ZERO
+        } else {
+            let gap = loaded.records[loaded.position]
+                .timestamp_ns
+                .saturating_sub(loaded.records[loaded.position - 1].timestamp_ns);
+            Duration::from_nanos((gap as f64 / loaded.speed) as u64)
+        };
+
+        Some((delay, generation))
+    }
+
+    // Take the next record unless a control came in while waiting for it. Also returns the status
+    // if that was the last record
+    fn advance(&self, generation: u64) -> Option<(Arc<Vec<Record>>, usize, Option<ReplayStatus>)> {
+        let mut state = self.state.lock().unwrap();
+        if state.generation != generation {
+            return None;
+        }
+
+        let loaded = state.loaded.as_mut()?;
+        let index = loaded.position;
+        loaded.position += 1;
+
+        let finished = (loaded.position >= loaded.records.len()).then(|| {
+            loaded.playing = false;
+            loaded.status()
+        });
+
+        Some((loaded.records.clone(), index, finished))
+    }
+
+    pub async fn run(self: Arc<Self>, core: Arc<AppCore>) {
+        loop {
+            let changed = self.changed.notified();
+            let Some((delay, generation)) = self.next() else {
+                changed.await;
+                continue;
+            };
+
+            if delay.is_zero() {
+                // Catching up can be a lot of records, don't hog the runtime
+                tokio::task::yield_now().await;
+            } else {
+                tokio::select! {
+                    _ = tokio::time::sleep(delay) => (),
+                    _ = changed => continue,
+                }
+            }
+
+            let Some((records, index, finished)) = self.advance(generation) else {
+                continue;
+            };
+            self.replay_record(&records[index], &core);
+
+            if let Some(status) = finished {
+                core.sink.emit_all("replay", status);
+            }
+        }
+    }
+
+    fn replay_record(&self, record: &Record, core: &AppCore) {
+        match record.mtype {
+            STRATEGY_LIST_RECORD => match AppResponse::decode(record.bytes.as_slice()) {
+                Ok(strategy_list) => core.sink.emit_all("strategy_list", strategy_list),
+                Err(e) => eprintln!("Failed to decode recorded strategy list: {}", e),
+            },
+            STREAMING_RECORD => match ChartRequest::decode(record.bytes.as_slice()) {
+                Ok(request) => {
+                    // Frontend selects what was streamed so the replayed chart shows up
+                    let scope = recorder::streaming_scope(request);
+                    self.state.lock().unwrap().streaming = scope.clone();
+                    core.sink.emit_all("replay_scope", scope);
+                }
+                Err(e) => eprintln!("Failed to decode recorded streaming scope: {}", e),
+            },
+            OVERALL_RECORD => match OverallRequest::decode(record.bytes.as_slice()) {
+                Ok(request) => self.session.overall_batch(&request.batch_id),
+                Err(e) => eprintln!("Failed to decode recorded overall batch: {}", e),
+            },
+            mtype => {
+                let Ok(mtype) = MessageType::try_from(mtype) else {
+                    eprintln!("Unknown message type {} in recording, skipping it", mtype);
+                    return;
+                };
+
+                match core.registry.replay(mtype, &record.bytes) {
+                    Some(Ok((event, policy, payload))) => {
+                        let streaming = self.state.lock().unwrap().streaming.clone();
+                        let origin = self.session.origin(mtype, streaming.as_ref(), &[]);
+                        self.session.record(mtype, event, &origin, &payload);
+
+                        let windows = core.sink.windows();
+                        let target = core.router.target(mtype, streaming.as_ref(), &windows);
+                        core.forwarder.forward(event, policy, target, payload);
+                    }
+                    Some(Err(e)) => eprintln!("Failed to replay recorded {:?}: {}", mtype, e),
+                    None => eprintln!("No handler for recorded {:?}, skipping it", mtype),
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,440 @@
+use std::collections::{HashMap, VecDeque};
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::sync::oneshot;
+use tradebot_protos::messages::enums::MessageType;
+
+use crate::error::{CommandError, RequestFailedSnafu, TimeoutSnafu};
+use crate::identity;
+
+// Requests are matched to their answers by the kind of data the services send back
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RequestKind {
+    // Chart and strategy from log requests, answered with a Chart
+    Chart,
+    // Overall and overall from log requests, answered with OverallStats
+    Overall,
+}
+
+impl RequestKind {
+    // Kind of request the message type answers, if any
+    pub fn answered_by(mtype: MessageType) -> Option<RequestKind> {
+        match mtype {
+            MessageType::Chart => Some(RequestKind::Chart),
+            MessageType::OverallStats => Some(RequestKind::Overall),
+            _ => None,
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            RequestKind::Chart => "chart request",
+            RequestKind::Overall => "overall request",
+        }
+    }
+}
+
+struct Outstanding {
+    request_id: u64,
+    key: String,
+    // App feeds the answer can come in on, any feed if empty
+    feed_ids: Vec<String>,
+    waiters: Vec<oneshot::Sender<Result<(), CommandError>>>,
+}
+
+impl Outstanding {
+    fn answered_on(&self, feed_id: &str) -> bool {
+        self.feed_ids.is_empty() || self.feed_ids.iter().any(|id| id == feed_id)
+    }
+}
+
+// Requests that were published but not answered yet. Each request is stamped with a request id
+// that is also its send time, and the invoke that published it waits until the answer arrives or
+// the request timeout runs out. A request for something that is already in flight is coalesced
+// into the one that was published, so views mounting together don't flood the services
+pub struct OutstandingRequests {
+    timeout: Duration,
+    last_request_id: AtomicU64,
+    outstanding: Mutex<HashMap<RequestKind, VecDeque<Outstanding>>>,
+}
+
+impl OutstandingRequests {
+    pub fn new(timeout: Duration) -> Self {
+        Self {
+            timeout,
+            last_request_id: AtomicU64::new(0),
+            outstanding: Mutex::new(HashMap::new()),
+        }
+    }
+
+    // Send time of the request, bumped if needed so every request gets its own id
+    fn next_request_id(&self) -> u64 {
+        let now = identity::timestamp_ns();
+
+        let mut last = self.last_request_id.load(Ordering::SeqCst);
+        loop {
+            let id = now.max(last + 1);
+            match self.last_request_id.compare_exchange(
+                last,
+                id,
+                Ordering::SeqCst,
+                Ordering::SeqCst,
+            ) {
+                Ok(_) => return id,
+                Err(current) => last = current,
+            }
+        }
+    }
+
+    // Publish a request with a fresh request id unless one for the same key is already in flight,
+    // then wait for the answer on one of the given app feeds
+    pub async fn request<F, Fut>(
+        &self,
+        kind: RequestKind,
+        key: String,
+        feed_ids: Vec<String>,
+        publish: F,
+    ) -> Result<(), CommandError>
+    where
+        F: FnOnce(u64) -> Fut,
+        Fut: Future<Output = Result<(), CommandError>>,
+    {
+        let (sender, receiver) = oneshot::channel();
+        let (request_id, in_flight) = {
+            let mut outstanding = self.outstanding.lock().unwrap();
+            let queue = outstanding.entry(kind).or_default();
+
+            match queue.iter_mut().find(|outstanding| outstanding.key == key) {
+                Some(outstanding) => {
+                    outstanding.waiters.push(sender);
+                    (outstanding.request_id, true)
+                }
+                None => {
+                    let request_id = self.next_request_id();
+                    queue.push_back(Outstanding {
+                        request_id,
+                        key,
+                        feed_ids,
+                        waiters: vec![sender],
+                    });
+                    (request_id, false)
+                }
+            }
+        };
+
+        if !in_flight {
+            if let Err(e) = publish(request_id).await {
+                // Nothing is coming back for this one, tell whoever joined it why
+                if let Some(failed) = self.remove(kind, request_id) {
+                    for waiter in failed.waiters {
+                        let _ = waiter.send(
+                            RequestFailedSnafu {
+                                kind: e.kind(),
+                                message: e.to_string(),
+                            }
+                            .fail(),
+                        );
+                    }
+                }
+                return Err(e);
+            }
+        }
+
+        match tokio::time::timeout(self.timeout, receiver).await {
+            Ok(Ok(result)) => result,
+            // Dropped because the request it joined timed out
+            Ok(Err(_)) | Err(_) => {
+                self.remove(kind, request_id);
+                TimeoutSnafu {
+                    request: kind.name(),
+                    timeout_ms: self.timeout.as_millis() as u64,
+                }
+                .fail()
+            }
+        }
+    }
+
+    // The answers don't say which request they belong to, services answer in order so they
+    // resolve the oldest outstanding request of their kind made for the feed they came in on
+    pub fn answered(&self, kind: RequestKind, feed_id: &str) {
+        let answered = {
+            let mut outstanding = self.outstanding.lock().unwrap();
+            outstanding.get_mut(&kind).and_then(|queue| {
+                let idx = queue.iter().position(|o| o.answered_on(feed_id))?;
+                queue.remove(idx)
+            })
+        };
+
+        if let Some(answered) = answered {
+            for waiter in answered.waiters {
+                let _ = waiter.send(Ok(()));
+            }
+        }
+    }
+
+    fn remove(&self, kind: RequestKind, request_id: u64) -> Option<Outstanding> {
+        let mut outstanding = self.outstanding.lock().unwrap();
+        let queue = outstanding.get_mut(&kind)?;
+        let idx = queue.iter().position(|o| o.request_id == request_id)?;
+        queue.remove(idx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicBool, AtomicU32};
+    use std::sync::Arc;
+    use std::time::Instant;
+
+    const FEED: &str = "127.0.0.1:7447";
+
+    fn feeds() -> Vec<String> {
+        vec![FEED.to_owned()]
+    }
+
+    // Stand in for a publish that has to wait on the network
+    async fn slow_publish(published: &AtomicU32) -> Result<(), CommandError> {
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        published.fetch_add(1, Ordering::SeqCst);
+        Ok(())
+    }
+
+    // Stand in for the services, answering every request of the kind as it comes in
+    fn answer_all(requests: Arc<OutstandingRequests>, kind: RequestKind) -> Arc<AtomicBool> {
+        let done = Arc::new(AtomicBool::new(false));
+        let answering = done.clone();
+        tokio::spawn(async move {
+            while !answering.load(Ordering::SeqCst) {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+                requests.answered(kind, FEED);
+            }
+        });
+
+        done
+    }
+
+    #[tokio::test]
+    async fn coalesces_requests_for_the_same_key() {
+        let requests = Arc::new(OutstandingRequests::new(Duration::from_secs(5)));
+        let published = Arc::new(AtomicU32::new(0));
+        let done = answer_all(requests.clone(), RequestKind::Overall);
+
+        let handles: Vec<_> = (0..20)
+            .map(|i| {
+                let requests = requests.clone();
+                let published = published.clone();
+                tokio::spawn(async move {
+                    let batch_id = format!("batch-{}", i % 2);
+                    requests
+                        .request(RequestKind::Overall, batch_id, feeds(), |_| {
+                            slow_publish(&published)
+                        })
+                        .await
+                })
+            })
+            .collect();
+
+        for handle in handles {
+            handle.await.unwrap().unwrap();
+        }
+        done.store(true, Ordering::SeqCst);
+
+        assert_eq!(published.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn answered_request_can_be_asked_again() {
+        let requests = Arc::new(OutstandingRequests::new(Duration::from_secs(5)));
+        let published = AtomicU32::new(0);
+        let done = answer_all(requests.clone(), RequestKind::Chart);
+
+        for _ in 0..2 {
+            requests
+                .request(RequestKind::Chart, "chart".to_owned(), feeds(), |_| {
+                    slow_publish(&published)
+                })
+                .await
+                .unwrap();
+        }
+        done.store(true, Ordering::SeqCst);
+
+        assert_eq!(published.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn answers_only_resolve_their_own_kind() {
+        let requests = Arc::new(OutstandingRequests::new(Duration::from_millis(100)));
+        let published = AtomicU32::new(0);
+        let done = answer_all(requests.clone(), RequestKind::Chart);
+
+        let result = requests
+            .request(RequestKind::Overall, "batch".to_owned(), feeds(), |_| {
+                slow_publish(&published)
+            })
+            .await;
+        done.store(true, Ordering::SeqCst);
+
+        assert!(matches!(result, Err(CommandError::Timeout { .. })));
+    }
+
+    #[tokio::test]
+    async fn times_out_and_forgets_unanswered_request() {
+        let requests = OutstandingRequests::new(Duration::from_millis(50));
+        let published = AtomicU32::new(0);
+
+        for _ in 0..2 {
+            let result = requests
+                .request(RequestKind::Overall, "batch".to_owned(), feeds(), |_| {
+                    slow_publish(&published)
+                })
+                .await;
+            assert!(matches!(result, Err(CommandError::Timeout { .. })));
+        }
+
+        assert_eq!(published.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn failed_publish_is_not_outstanding() {
+        let requests = Arc::new(OutstandingRequests::new(Duration::from_secs(5)));
+        let published = AtomicU32::new(0);
+
+        let failed = requests
+            .request(
+                RequestKind::Overall,
+                "batch".to_owned(),
+                feeds(),
+                |_| async { Err(CommandError::NotConnected) },
+            )
+            .await;
+        assert!(matches!(failed, Err(CommandError::NotConnected)));
+
+        let done = answer_all(requests.clone(), RequestKind::Overall);
+        requests
+            .request(RequestKind::Overall, "batch".to_owned(), feeds(), |_| {
+                slow_publish(&published)
+            })
+            .await
+            .unwrap();
+        done.store(true, Ordering::SeqCst);
+
+        assert_eq!(published.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn joined_requests_get_the_publish_error() {
+        let requests = Arc::new(OutstandingRequests::new(Duration::from_secs(5)));
+
+        let failing = {
+            let requests = requests.clone();
+            tokio::spawn(async move {
+                requests
+                    .request(RequestKind::Chart, "chart".to_owned(), feeds(), |_| async {
+                        tokio::time::sleep(Duration::from_millis(50)).await;
+                        Err(CommandError::NotConnected)
+                    })
+                    .await
+            })
+        };
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        let joined = requests
+            .request(RequestKind::Chart, "chart".to_owned(), feeds(), |_| async {
+                Ok(())
+            })
+            .await;
+
+        assert!(matches!(
+            failing.await.unwrap(),
+            Err(CommandError::NotConnected)
+        ));
+        let joined = joined.unwrap_err();
+        assert_eq!(joined.kind(), "not_connected");
+        assert_eq!(joined.to_string(), CommandError::NotConnected.to_string());
+    }
+
+    #[tokio::test]
+    async fn answers_from_other_feeds_are_ignored() {
+        let requests = Arc::new(OutstandingRequests::new(Duration::from_millis(100)));
+        let published = AtomicU32::new(0);
+        let other = {
+            let requests = requests.clone();
+            tokio::spawn(async move {
+                tokio::time::sleep(Duration::from_millis(60)).await;
+                requests.answered(RequestKind::Chart, "127.0.0.1:7448");
+            })
+        };
+
+        let result = requests
+            .request(RequestKind::Chart, "chart".to_owned(), feeds(), |_| {
+                slow_publish(&published)
+            })
+            .await;
+        other.await.unwrap();
+
+        assert!(matches!(result, Err(CommandError::Timeout { .. })));
+    }
+
+    #[tokio::test]
+    async fn request_ids_are_unique() {
+        let requests = OutstandingRequests::new(Duration::from_secs(5));
+        let ids: Vec<_> = (0..1000).map(|_| requests.next_request_id()).collect();
+
+        assert!(ids.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
+    // A single threaded runtime is stalled by any blocking call, so a ticker running alongside
+    // concurrent requests only keeps up if none of them block
+    #[tokio::test(flavor = "current_thread")]
+    async fn runtime_stays_responsive_under_concurrent_requests() {
+        let requests = Arc::new(OutstandingRequests::new(Duration::from_secs(5)));
+        let published = Arc::new(AtomicU32::new(0));
+        let done = answer_all(requests.clone(), RequestKind::Overall);
+        let ticks = Arc::new(AtomicU32::new(0));
+
+        let ticker = {
+            let done = done.clone();
+            let ticks = ticks.clone();
+            tokio::spawn(async move {
+                let mut interval = tokio::time::interval(Duration::from_millis(5));
+                while !done.load(Ordering::SeqCst) {
+                    interval.tick().await;
+                    ticks.fetch_add(1, Ordering::SeqCst);
+                }
+            })
+        };
+
+        let started = Instant::now();
+        let handles: Vec<_> = (0..50)
+            .map(|i| {
+                let requests = requests.clone();
+                let published = published.clone();
+                tokio::spawn(async move {
+                    requests
+                        .request(
+                            RequestKind::Overall,
+                            format!("batch-{}", i),
+                            feeds(),
+                            |_| slow_publish(&published),
+                        )
+                        .await
+                })
+            })
+            .collect();
+
+        for handle in handles {
+            handle.await.unwrap().unwrap();
+        }
+        let elapsed = started.elapsed();
+
+        done.store(true, Ordering::SeqCst);
+        ticker.await.unwrap();
+
+        // 50 publishes of 50ms each would take 2.5s if they ran one after the other
+        assert_eq!(published.load(Ordering::SeqCst), 50);
+        assert!(elapsed < Duration::from_millis(1500), "took {:?}", elapsed);
+        assert!(ticks.load(Ordering::SeqCst) >= 5);
+    }
+}
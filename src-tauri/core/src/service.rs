@@ -0,0 +1,99 @@
+use tokio::sync::Mutex;
+use tradebot_protos::messages::{
+    AppRequest, AppResponse, ChartRequest, OverallFromLogRequest, OverallRequest,
+    ReadFromDirRequest, ReadFromDirResponse, RunYaml, StrategyFromLogRequest,
+};
+use zenoh_node::builder::NodeBuilder;
+use zenoh_node::error::NodeError;
+use zenoh_node::node::{Abort, Node, Publisher, Subscribe};
+
+use crate::config::ServiceConfig;
+use crate::topics::{Topic, TopicConfig};
+
+pub struct ServicePublishers {
+    pub app_request: Publisher<AppRequest>,
+    pub run_yaml: Publisher<RunYaml>,
+    pub chart_request: Publisher<ChartRequest>,
+    pub overall_request: Publisher<OverallRequest>,
+    pub read_from_dir_request: Publisher<ReadFromDirRequest>,
+    pub strategy_from_log_request: Publisher<StrategyFromLogRequest>,
+    pub overall_from_log_request: Publisher<OverallFromLogRequest>,
+}
+
+// The one connection to TradeBot services. Owns the service node, every publisher the app uses
+// and the subscriber listening for service responses
+pub struct ServiceConnection {
+    node: Node,
+    topics: TopicConfig,
+    pub publishers: ServicePublishers,
+    subscriber: Mutex<Option<Box<dyn Abort>>>,
+}
+
+impl ServiceConnection {
+    pub async fn connect(service_config: &ServiceConfig) -> Result<Self, NodeError> {
+        let mut builder = NodeBuilder::new();
+        if let Some(config) = &service_config.zenoh_config_path {
+            builder.set_config_path(config);
+        }
+
+        builder.set_network((service_config.ip.clone(), service_config.port));
+        let node = builder.build().await?;
+
+        // Create publishers on the service node. Use topics from service config if they exist, otherwise use hard coded topic
+        let topics = service_config.topic_config();
+        let publishers = ServicePublishers {
+            app_request: node.new_publisher(topics.get(Topic::AppRequest)).await?,
+            run_yaml: node.new_publisher(topics.get(Topic::RunYaml)).await?,
+            chart_request: node.new_publisher(topics.get(Topic::ChartRequest)).await?,
+            overall_request: node
+                .new_publisher(topics.get(Topic::OverallRequest))
+                .await?,
+            read_from_dir_request: node
+                .new_publisher(topics.get(Topic::ReadFromDirRequest))
+                .await?,
+            strategy_from_log_request: node
+                .new_publisher(topics.get(Topic::StrategyFromLogRequest))
+                .await?,
+            overall_from_log_request: node
+                .new_publisher(topics.get(Topic::OverallFromLogRequest))
+                .await?,
+        };
+
+        Ok(Self {
+            node,
+            topics,
+            publishers,
+            subscriber: Mutex::new(None),
+        })
+    }
+
+    // Subscribe to service responses
+    pub async fn subscribe<S>(&self, service_subscriber: S) -> Result<(), NodeError>
+    where
+        S: Subscribe<AppResponse> + Subscribe<ReadFromDirResponse> + Send + 'static,
+    {
+        let mut subscriber = self.node.new_subscriber(service_subscriber).await?;
+
+        self.node
+            .subscribe::<AppResponse>(self.topics.get(Topic::AppResponse), &mut subscriber)
+            .await?;
+
+        self.node
+            .subscribe::<ReadFromDirResponse>(
+                self.topics.get(Topic::ReadFromDirResponse),
+                &mut subscriber,
+            )
+            .await?;
+
+        *self.subscriber.lock().await = Some(Box::new(subscriber));
+
+        Ok(())
+    }
+
+    // Stop listening for service responses, the node is closed once the connection is dropped
+    pub async fn close(&self) {
+        if let Some(subscriber) = self.subscriber.lock().await.take() {
+            subscriber.abort();
+        }
+    }
+}
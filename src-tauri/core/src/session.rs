@@ -0,0 +1,266 @@
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::sync::Mutex;
+use tradebot_protos::messages::enums::MessageType;
+
+use crate::windows::{self, WindowScope};
+
+// Messages kept for one series before the oldest are dropped
+const MAX_SERIES_LEN: usize = 100_000;
+
+// Message types where every message counts, like candles or fills. For everything else only the
+// latest message is kept
+fn is_series(mtype: MessageType) -> bool {
+    matches!(
+        mtype,
+        MessageType::Candle
+            | MessageType::Point
+            | MessageType::Rectangle
+            | MessageType::Advice
+            | MessageType::Order
+            | MessageType::OrderFilled
+            | MessageType::TotalPnlRealized
+            | MessageType::PositionPnlRealized
+            | MessageType::PositionPnlUnrealized
+    )
+}
+
+enum Retained {
+    Latest(Value),
+    Series(VecDeque<Value>),
+}
+
+#[derive(Default)]
+struct Entries(HashMap<&'static str, Retained>);
+
+impl Entries {
+    fn record(&mut self, mtype: MessageType, event: &'static str, payload: &Value) {
+        if !is_series(mtype) {
+            self.0.insert(event, Retained::Latest(payload.clone()));
+            return;
+        }
+
+        let entry = self
+            .0
+            .entry(event)
+            .or_insert_with(|| Retained::Series(VecDeque::new()));
+        if let Retained::Series(series) = entry {
+            if series.len() >= MAX_SERIES_LEN {
+                series.pop_front();
+            }
+            series.push_back(payload.clone());
+        }
+    }
+
+    fn snapshot(&self, snapshot: &mut BTreeMap<&'static str, Value>) {
+        for (event, retained) in &self.0 {
+            let value = match retained {
+                Retained::Latest(value) => value.clone(),
+                Retained::Series(series) => Value::Array(series.iter().cloned().collect()),
+            };
+            snapshot.insert(event, value);
+        }
+    }
+}
+
+// Batch and strategy an app feed message is for, as far as the app can tell. Unknown when nothing
+// is being streamed or no overall request was made
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Origin {
+    // App feed the message came in on
+    pub feed_id: Option<String>,
+    pub batch_id: Option<String>,
+    pub strategy_id: Option<String>,
+    pub symbol: Option<String>,
+    pub period_s: Option<u32>,
+}
+
+impl Origin {
+    pub fn streamed(scope: &WindowScope) -> Self {
+        Self {
+            feed_id: None,
+            batch_id: Some(scope.batch_id.clone()),
+            strategy_id: Some(scope.strategy_id.clone()),
+            symbol: Some(scope.symbol.clone()),
+            period_s: Some(scope.period_s),
+        }
+    }
+
+    // Strategy chart the message is for, None for batch messages
+    pub fn scope(&self) -> Option<WindowScope> {
+        Some(WindowScope {
+            batch_id: self.batch_id.clone()?,
+            strategy_id: self.strategy_id.clone()?,
+            symbol: self.symbol.clone()?,
+            period_s: self.period_s?,
+        })
+    }
+}
+
+#[derive(Default)]
+struct SessionState {
+    // Batch the last overall request was for, overall messages don't say which batch they are for
+    overall_batch: Option<String>,
+    batches: HashMap<String, Entries>,
+    strategies: HashMap<WindowScope, Entries>,
+}
+
+// Everything received on the app feeds this session, per batch and per charted strategy, so views
+// that mount late or a reloaded frontend can hydrate without asking TradeBot again
+#[derive(Default)]
+pub struct SessionStore {
+    state: Mutex<SessionState>,
+}
+
+impl SessionStore {
+    pub fn new() -> Self {
+        Self {
+            state: Mutex::new(SessionState::default()),
+        }
+    }
+
+    // Forget everything kept
+    pub fn clear(&self) {
+        *self.state.lock().unwrap() = SessionState::default();
+    }
+
+    // Forget everything kept for the batches and their strategies
+    pub fn forget_batches(&self, batch_ids: &[String]) {
+        let mut state = self.state.lock().unwrap();
+        state
+            .batches
+            .retain(|batch_id, _| !batch_ids.contains(batch_id));
+        state
+            .strategies
+            .retain(|scope, _| !batch_ids.contains(&scope.batch_id));
+    }
+
+    // Overall messages received from now on are for the batch
+    pub fn overall_batch(&self, batch_id: &str) {
+        self.state.lock().unwrap().overall_batch = Some(batch_id.to_owned());
+    }
+
+    // Who a message received on a feed advertising batch_ids is for. Strategy messages are for
+    // whatever is streamed on the feed, everything else for the batch of the last overall request,
+    // or the batch of the feed if it only has one and that request wasn't for it
+    pub fn origin(
+        &self,
+        mtype: MessageType,
+        streaming: Option<&WindowScope>,
+        batch_ids: &[String],
+    ) -> Origin {
+        if windows::strategy_scoped(mtype) {
+            return streaming.map(Origin::streamed).unwrap_or_default();
+        }
+
+        let batch_id = match self.state.lock().unwrap().overall_batch.clone() {
+            Some(batch_id) if batch_ids.is_empty() || batch_ids.contains(&batch_id) => {
+                Some(batch_id)
+            }
+            _ if batch_ids.len() == 1 => Some(batch_ids[0].clone()),
+            // Read from logs, those batches aren't advertised by any feed
+            overall_batch => overall_batch,
+        };
+
+        Origin {
+            batch_id,
+            ..Default::default()
+        }
+    }
+
+    // Keep a message forwarded to the frontend. Messages the app can't tell the origin of are dropped
+    pub fn record(
+        &self,
+        mtype: MessageType,
+        event: &'static str,
+        origin: &Origin,
+        payload: &Value,
+    ) {
+        let mut state = self.state.lock().unwrap();
+
+        if windows::strategy_scoped(mtype) {
+            let Some(scope) = origin.scope() else {
+                return;
+            };
+
+            let entries = state.strategies.entry(scope).or_default();
+            // Chart is the start of a stream, anything kept from an earlier one would be repeated
+            if mtype == MessageType::Chart {
+                entries.0.clear();
+            }
+            entries.record(mtype, event, payload);
+        } else {
+            let Some(batch_id) = origin.batch_id.clone() else {
+                return;
+            };
+
+            state
+                .batches
+                .entry(batch_id)
+                .or_default()
+                .record(mtype, event, payload);
+        }
+    }
+
+    // Everything kept for the batch and, if given, the strategy, keyed by event name. Series are
+    // arrays of every message, everything else the latest message
+    pub fn snapshot(
+        &self,
+        batch_id: &str,
+        scope: Option<&WindowScope>,
+    ) -> BTreeMap<&'static str, Value> {
+        let state = self.state.lock().unwrap();
+
+        let mut snapshot = BTreeMap::new();
+        if let Some(entries) = state.batches.get(batch_id) {
+            entries.snapshot(&mut snapshot);
+        }
+        if let Some(entries) = scope.and_then(|scope| state.strategies.get(scope)) {
+            entries.snapshot(&mut snapshot);
+        }
+
+        snapshot
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn scope(batch_id: &str) -> WindowScope {
+        WindowScope {
+            batch_id: batch_id.to_owned(),
+            strategy_id: "sma".to_owned(),
+            symbol: "SPY".to_owned(),
+            period_s: 60,
+        }
+    }
+
+    #[test]
+    fn forgotten_batches_are_evicted_with_their_strategies() {
+        let session = SessionStore::new();
+        for batch_id in ["live", "backtest"] {
+            let origin = Origin::streamed(&scope(batch_id));
+            session.record(MessageType::Candle, "candle", &origin, &json!(1));
+            session.record(
+                MessageType::OverallStats,
+                "overall_stats",
+                &origin,
+                &json!(2),
+            );
+        }
+
+        session.forget_batches(&["backtest".to_owned()]);
+
+        assert!(session
+            .snapshot("backtest", Some(&scope("backtest")))
+            .is_empty());
+        assert_eq!(
+            session.snapshot("live", Some(&scope("live"))).len(),
+            2,
+            "other batches are kept"
+        );
+    }
+}
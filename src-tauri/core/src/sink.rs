@@ -0,0 +1,34 @@
+use serde::Serialize;
+use serde_json::Value;
+use tradebot_protos::messages::enums::MessageType;
+
+use crate::session::Origin;
+use crate::windows::Target;
+
+// Where events for the frontend go. The Tauri app emits them to its windows, a headless frontend can
+// print them and tests can collect them
+pub trait EventSink: Send + Sync + 'static {
+    // Send an event to the windows in target
+    fn emit(&self, event: &str, target: &Target, payload: Value);
+
+    // Labels of every open window, strategy streams are only sent to the ones showing them
+    fn windows(&self) -> Vec<String>;
+
+    // Every app feed message as it comes in, before it is batched into events. Frontends that only
+    // render events can ignore it
+    fn message(&self, _mtype: MessageType, _origin: &Origin, _payload: &Value) {}
+}
+
+impl dyn EventSink {
+    // Send an event to every window
+    pub fn emit_all<S: Serialize>(&self, event: &str, payload: S) {
+        self.emit_to(event, &Target::All, payload);
+    }
+
+    pub fn emit_to<S: Serialize>(&self, event: &str, target: &Target, payload: S) {
+        match serde_json::to_value(payload) {
+            Ok(payload) => self.emit(event, target, payload),
+            Err(e) => eprintln!("Failed to serialize {} for the frontend: {}", event, e),
+        }
+    }
+}
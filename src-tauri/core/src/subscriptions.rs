@@ -0,0 +1,349 @@
+use async_trait::async_trait;
+use prost::DecodeError;
+use serde::Serialize;
+use serde_json::Value;
+use snafu::{ResultExt, Snafu};
+use std::any::{type_name, TypeId};
+use std::collections::{BTreeMap, HashMap};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use tokio::sync::oneshot;
+use tradebot_protos::messages::enums::MessageType;
+use tradebot_protos::messages::{
+    Advice, AlgoChart, Candle, Chart, Order, OrderFilled, OrderList, OverallDayStats, OverallStats,
+    PnlCalendar, PnlHour, Point, PositionPnlRealized, PositionPnlRealizedList,
+    PositionPnlUnrealized, PositionStats, Rectangle, TotalPnl, TotalPnlRealized,
+    TotalPnlUnrealized,
+};
+use zenoh_node::error::NodeError;
+use zenoh_node::node::{Node, Subscribe, Subscriber, SubscriberError};
+
+use crate::app::AppCore;
+use crate::forwarder::{EventForwarder, ForwardPolicy};
+use crate::health::HealthMonitor;
+use crate::recorder::Recorder;
+use crate::requests::{OutstandingRequests, RequestKind};
+use crate::session::{Origin, SessionStore};
+use crate::sink::EventSink;
+use crate::windows::WindowRouter;
+
+type SubscribeFuture<'a> = Pin<Box<dyn Future<Output = Result<(), NodeError>> + Send + 'a>>;
+type SubscribeFn =
+    for<'a> fn(&'a Node, &'a str, &'a mut Subscriber<AppSubscriber>) -> SubscribeFuture<'a>;
+
+type ReplayFn = fn(&[u8]) -> Result<Value, ReplayError>;
+
+// Why a recorded message couldn't be replayed
+#[derive(Debug, Snafu)]
+pub enum ReplayError {
+    #[snafu(display("Failed to decode it: {}", source))]
+    Decode { source: DecodeError },
+
+    #[snafu(display("Failed to serialize it for the frontend: {}", source))]
+    Serialize { source: serde_json::Error },
+}
+
+fn subscribe_to<'a, T>(
+    node: &'a Node,
+    topic: &'a str,
+    subscriber: &'a mut Subscriber<AppSubscriber>,
+) -> SubscribeFuture<'a>
+where
+    T: prost::Message + Default + Serialize + Clone + 'static,
+{
+    Box::pin(node.subscribe::<T>(topic, subscriber))
+}
+
+fn replay_to<T>(bytes: &[u8]) -> Result<Value, ReplayError>
+where
+    T: prost::Message + Default + Serialize + Clone + 'static,
+{
+    let msg = T::decode(bytes).context(DecodeSnafu)?;
+    serde_json::to_value(msg).context(SerializeSnafu)
+}
+
+// Every app feed message type the frontend can render and the event it is forwarded as
+#[derive(Default)]
+pub struct SubscriptionRegistry {
+    by_mtype: HashMap<MessageType, (SubscribeFn, ReplayFn, &'static str, ForwardPolicy)>,
+    by_type: HashMap<TypeId, (MessageType, &'static str, ForwardPolicy)>,
+}
+
+impl SubscriptionRegistry {
+    pub fn new() -> Self {
+        Self {
+            by_mtype: HashMap::new(),
+            by_type: HashMap::new(),
+        }
+    }
+
+    // Registry of everything the frontend listens for. Adding a new proto type to the app feed
+    // only needs a line here and a listener on the frontend. Batched events reach the frontend as
+    // arrays, see EventForwarder
+    pub fn app() -> Self {
+        use ForwardPolicy::{Batch, Immediate, Latest};
+
+        let mut registry = Self::new();
+        registry
+            .register::<Chart>(MessageType::Chart, "chart", Immediate)
+            .register::<AlgoChart>(MessageType::AlgoChart, "algo_chart", Immediate)
+            .register::<PositionStats>(MessageType::PositionStats, "position_stats", Immediate)
+            .register::<OrderList>(MessageType::OrderList, "order_list", Immediate)
+            .register::<Candle>(MessageType::Candle, "update_candle", Batch)
+            .register::<Point>(MessageType::Point, "point", Batch)
+            .register::<Advice>(MessageType::Advice, "advice", Immediate)
+            .register::<Order>(MessageType::Order, "order", Immediate)
+            .register::<OrderFilled>(MessageType::OrderFilled, "order_filled", Immediate)
+            .register::<TotalPnl>(MessageType::TotalPnl, "total_pnl", Immediate)
+            .register::<TotalPnlRealized>(
+                MessageType::TotalPnlRealized,
+                "total_pnl_realized",
+                Batch,
+            )
+            .register::<TotalPnlUnrealized>(
+                MessageType::TotalPnlUnrealized,
+                "total_pnl_unrealized",
+                Latest,
+            )
+            .register::<PositionPnlRealized>(
+                MessageType::PositionPnlRealized,
+                "pos_pnl_realized",
+                Batch,
+            )
+            .register::<PositionPnlRealizedList>(
+                MessageType::PositionPnlRealizedList,
+                "pos_pnl_realized_list",
+                Immediate,
+            )
+            .register::<PositionPnlUnrealized>(
+                MessageType::PositionPnlUnrealized,
+                "pos_pnl_unrealized",
+                Batch,
+            )
+            .register::<OverallStats>(MessageType::OverallStats, "overall_stats", Immediate)
+            .register::<OverallDayStats>(
+                MessageType::OverallDayStats,
+                "overall_day_stats",
+                Immediate,
+            )
+            .register::<PnlCalendar>(MessageType::PnlCalendar, "pnl_calendar", Immediate)
+            .register::<PnlHour>(MessageType::PnlHour, "pnl_hour", Immediate)
+            .register::<Rectangle>(MessageType::Rectangle, "rectangle", Batch);
+
+        registry
+    }
+
+    // Subscribe to topics of the message type and forward every message to the frontend as event
+    pub fn register<T>(
+        &mut self,
+        mtype: MessageType,
+        event: &'static str,
+        policy: ForwardPolicy,
+    ) -> &mut Self
+    where
+        T: prost::Message + Default + Serialize + Clone + 'static,
+    {
+        self.by_mtype
+            .insert(mtype, (subscribe_to::<T>, replay_to::<T>, event, policy));
+        self.by_type
+            .insert(TypeId::of::<T>(), (mtype, event, policy));
+
+        self
+    }
+
+    // Subscribe to a topic advertised in the app response. Returns None if nothing is registered
+    // for the message type
+    pub async fn subscribe(
+        &self,
+        mtype: MessageType,
+        node: &Node,
+        topic: &str,
+        subscriber: &mut Subscriber<AppSubscriber>,
+    ) -> Option<Result<(), NodeError>> {
+        let (subscribe, ..) = self.by_mtype.get(&mtype)?;
+        Some(subscribe(node, topic, subscriber).await)
+    }
+
+    // Decode a recorded message into the event it was forwarded as, its forward policy and payload.
+    // Returns None if nothing is registered for the message type
+    pub fn replay(
+        &self,
+        mtype: MessageType,
+        bytes: &[u8],
+    ) -> Option<Result<(&'static str, ForwardPolicy, Value), ReplayError>> {
+        let (_, replay, event, policy) = self.by_mtype.get(&mtype)?;
+        Some(replay(bytes).map(|payload| (*event, *policy, payload)))
+    }
+
+    // Every registered message type
+    pub fn mtypes(&self) -> Vec<MessageType> {
+        self.by_mtype.keys().copied().collect()
+    }
+
+    fn lookup<T: 'static>(&self) -> Option<(MessageType, &'static str, ForwardPolicy)> {
+        self.by_type.get(&TypeId::of::<T>()).copied()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TopicStatus {
+    // Subscribed and forwarded to the frontend
+    Subscribed,
+    // Known message type the app can't render yet
+    Unsupported,
+    // Message type this build of the app doesn't know about
+    UnknownType,
+    // Subscribing to the topic failed
+    Failed,
+}
+
+// A topic advertised in the app response and what the app did with it
+#[derive(Debug, Clone, Serialize)]
+pub struct AdvertisedTopic {
+    // App feed the topic was advertised for
+    pub feed_id: String,
+    pub topic: String,
+    pub mtype_id: i32,
+    pub mtype: Option<MessageType>,
+    pub status: TopicStatus,
+    pub error: Option<String>,
+}
+
+impl AdvertisedTopic {
+    pub fn new(feed_id: &str, topic: &str, mtype_id: i32, status: TopicStatus) -> Self {
+        Self {
+            feed_id: feed_id.to_owned(),
+            topic: topic.to_owned(),
+            mtype_id,
+            mtype: MessageType::try_from(mtype_id).ok(),
+            status,
+            error: None,
+        }
+    }
+}
+
+// Advertised vs subscribed topics of every app feed, sent to the frontend as the topic_inventory
+// event so new streams the app can't render yet don't go unnoticed
+#[derive(Default)]
+pub struct TopicInventory {
+    // By feed id
+    topics: Mutex<BTreeMap<String, Vec<AdvertisedTopic>>>,
+}
+
+impl TopicInventory {
+    pub fn new() -> Self {
+        Self {
+            topics: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    // Topics from the app response the feed was followed from
+    pub fn set(&self, feed_id: &str, topics: Vec<AdvertisedTopic>) {
+        self.topics
+            .lock()
+            .unwrap()
+            .insert(feed_id.to_owned(), topics);
+    }
+
+    pub fn remove(&self, feed_id: &str) {
+        self.topics.lock().unwrap().remove(feed_id);
+    }
+
+    pub fn snapshot(&self) -> Vec<AdvertisedTopic> {
+        self.topics
+            .lock()
+            .unwrap()
+            .values()
+            .flatten()
+            .cloned()
+            .collect()
+    }
+}
+
+// App subscriber that will subscribe to protobuf messages and just relay them to the front end.
+// Since front end uses the same protobufs as well, nothing else we need to do
+pub struct AppSubscriber {
+    // App feed the subscriber is for and the batches it advertised
+    feed_id: String,
+    batch_ids: Vec<String>,
+    sink: Arc<dyn EventSink>,
+    registry: Arc<SubscriptionRegistry>,
+    router: Arc<WindowRouter>,
+    forwarder: Arc<EventForwarder>,
+    health_monitor: Arc<HealthMonitor>,
+    outstanding_requests: Arc<OutstandingRequests>,
+    session: Arc<SessionStore>,
+    recorder: Arc<Recorder>,
+    // Never sent on. Dropped with the subscriber task, which is what closing a feed waits for
+    _finished: oneshot::Sender<()>,
+}
+
+impl AppSubscriber {
+    pub fn new(
+        core: &AppCore,
+        feed_id: String,
+        batch_ids: Vec<String>,
+        finished: oneshot::Sender<()>,
+    ) -> Self {
+        Self {
+            feed_id,
+            batch_ids,
+            sink: core.sink.clone(),
+            registry: core.registry.clone(),
+            router: core.router.clone(),
+            forwarder: core.forwarder.clone(),
+            health_monitor: core.health_monitor.clone(),
+            outstanding_requests: core.outstanding_requests.clone(),
+            session: core.session.clone(),
+            recorder: core.recorder.clone(),
+            _finished: finished,
+        }
+    }
+}
+
+#[async_trait]
+impl<T> Subscribe<T> for AppSubscriber
+where
+    T: prost::Message + Serialize + Clone + Send + 'static,
+{
+    async fn on_data(&mut self, msg: T) -> Result<(), SubscriberError> {
+        let Some((mtype, event, policy)) = self.registry.lookup::<T>() else {
+            eprintln!(
+                "Received {} that is not registered, dropping it",
+                type_name::<T>()
+            );
+            return Ok(());
+        };
+
+        self.health_monitor.seen_mtype(mtype);
+        if let Some(kind) = RequestKind::answered_by(mtype) {
+            self.outstanding_requests.answered(kind, &self.feed_id);
+        }
+
+        let streaming = self.router.streamed(&self.feed_id);
+        self.recorder.record(mtype, &msg, streaming.clone());
+
+        let payload = match serde_json::to_value(msg) {
+            Ok(payload) => payload,
+            Err(e) => {
+                eprintln!("Failed to serialize {} for the frontend: {}", event, e);
+                return Ok(());
+            }
+        };
+        let origin = Origin {
+            feed_id: Some(self.feed_id.clone()),
+            ..self
+                .session
+                .origin(mtype, streaming.as_ref(), &self.batch_ids)
+        };
+        self.sink.message(mtype, &origin, &payload);
+        self.session.record(mtype, event, &origin, &payload);
+
+        let windows = self.sink.windows();
+        let target = self.router.target(mtype, streaming.as_ref(), &windows);
+        self.forwarder.forward(event, policy, target, payload);
+        Ok(())
+    }
+}
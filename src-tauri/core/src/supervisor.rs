@@ -0,0 +1,321 @@
+use serde::Serialize;
+use snafu::ResultExt;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::{watch, Notify, RwLock};
+use tradebot_protos::messages::{AppRequest, AppResponse, ReadFromDirResponse};
+use zenoh_node::error::NodeError;
+use zenoh_node::node::Subscribe;
+
+use crate::config::ServiceConfig;
+use crate::error::{CommandError, PublishSnafu};
+use crate::identity;
+use crate::service::ServiceConnection;
+use crate::sink::EventSink;
+
+const INITIAL_BACKOFF: Duration = Duration::from_millis(500);
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+// Consecutive publish failures before we give up on the connection and rebuild it
+const MAX_PUBLISH_FAILURES: u32 = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConnectionState {
+    Connecting,
+    Connected,
+    Degraded,
+    Disconnected,
+}
+
+// Sent to the frontend as the connection_status event whenever the state changes
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ConnectionStatus {
+    pub state: ConnectionState,
+    pub attempt: u32,
+    pub error: Option<String>,
+    pub retry_in_ms: Option<u64>,
+}
+
+impl ConnectionStatus {
+    fn new(state: ConnectionState, attempt: u32) -> Self {
+        Self {
+            state,
+            attempt,
+            error: None,
+            retry_in_ms: None,
+        }
+    }
+}
+
+// Keeps the service connection alive. Builds the connection, subscribes to service responses and
+// rebuilds everything with backoff whenever the connection fails
+pub struct ServiceLink {
+    service_config: ServiceConfig,
+    client_id: String,
+    connection: RwLock<Option<Arc<ServiceConnection>>>,
+    status: watch::Sender<ConnectionStatus>,
+    publish_failures: AtomicU32,
+    // Degraded by the health monitor because the app service stopped answering
+    unresponsive: AtomicBool,
+    // Services answered on the current connection
+    answered: AtomicBool,
+    // Why the current connection should be rebuilt, if it should. Reset with every new connection
+    // so a request made for an old one can't tear down the next
+    reconnect_requested: Mutex<Option<String>>,
+    reconnect: Notify,
+}
+
+impl ServiceLink {
+    pub fn new(service_config: ServiceConfig, client_id: String) -> Self {
+        let (status, _) = watch::channel(ConnectionStatus::new(ConnectionState::Connecting, 0));
+
+        Self {
+            service_config,
+            client_id,
+            connection: RwLock::new(None),
+            status,
+            publish_failures: AtomicU32::new(0),
+            unresponsive: AtomicBool::new(false),
+            answered: AtomicBool::new(false),
+            reconnect_requested: Mutex::new(None),
+            reconnect: Notify::new(),
+        }
+    }
+
+    // Run forever, creating a new service subscriber from new_subscriber on every (re)connect
+    pub async fn supervise<S, F>(self: Arc<Self>, sink: Arc<dyn EventSink>, new_subscriber: F)
+    where
+        S: Subscribe<AppResponse> + Subscribe<ReadFromDirResponse> + Send + 'static,
+        F: Fn() -> S,
+    {
+        // Forward every status change to the frontend
+        let mut status = self.status.subscribe();
+        tokio::spawn(async move {
+            while status.changed().await.is_ok() {
+                let current = status.borrow_and_update().clone();
+                sink.emit_all("connection_status", current);
+            }
+        });
+
+        let mut attempt = 0;
+        let mut backoff = INITIAL_BACKOFF;
+
+        loop {
+            attempt += 1;
+            self.set_status(ConnectionStatus::new(ConnectionState::Connecting, attempt));
+
+            match self.connect(new_subscriber()).await {
+                Ok(connection) => {
+                    self.publish_failures.store(0, Ordering::SeqCst);
+                    self.unresponsive.store(false, Ordering::SeqCst);
+                    self.answered.store(false, Ordering::SeqCst);
+                    *self.reconnect_requested.lock().unwrap() = None;
+                    *self.connection.write().await = Some(connection);
+                    self.set_status(ConnectionStatus::new(ConnectionState::Connected, attempt));
+
+                    // Stay connected until publishing keeps failing or services stop answering
+                    let reason = self.reconnect_requested().await;
+
+                    if let Some(connection) = self.connection.write().await.take() {
+                        connection.close().await;
+                    }
+
+                    if self.answered.load(Ordering::SeqCst) {
+                        attempt = 0;
+                        backoff = INITIAL_BACKOFF;
+                        continue;
+                    }
+
+                    // Services never answered on it, back off like a failed connect
+                    self.set_status(ConnectionStatus {
+                        state: ConnectionState::Disconnected,
+                        attempt,
+                        error: Some(reason),
+                        retry_in_ms: Some(backoff.as_millis() as u64),
+                    });
+
+                    tokio::time::sleep(backoff).await;
+                    backoff = (backoff * 2).min(MAX_BACKOFF);
+                }
+                Err(e) => {
+                    self.set_status(ConnectionStatus {
+                        state: ConnectionState::Disconnected,
+                        attempt,
+                        error: Some(e),
+                        retry_in_ms: Some(backoff.as_millis() as u64),
+                    });
+
+                    tokio::time::sleep(backoff).await;
+                    backoff = (backoff * 2).min(MAX_BACKOFF);
+                }
+            }
+        }
+    }
+
+    async fn connect<S>(&self, service_subscriber: S) -> Result<Arc<ServiceConnection>, String>
+    where
+        S: Subscribe<AppResponse> + Subscribe<ReadFromDirResponse> + Send + 'static,
+    {
+        let connection = ServiceConnection::connect(&self.service_config)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        connection
+            .subscribe(service_subscriber)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        // Let backend know the app is up and running
+        connection
+            .publishers
+            .app_request
+            .publish(self.app_request())
+            .await
+            .map_err(|e| e.to_string())?;
+
+        Ok(Arc::new(connection))
+    }
+
+    // Tear down the current connection and build a new one. Only the first request per connection
+    // counts
+    fn request_reconnect(&self, reason: String) {
+        let mut requested = self.reconnect_requested.lock().unwrap();
+        if requested.is_none() {
+            *requested = Some(reason);
+            drop(requested);
+            self.reconnect.notify_waiters();
+        }
+    }
+
+    async fn reconnect_requested(&self) -> String {
+        loop {
+            let notified = self.reconnect.notified();
+            let requested = self.reconnect_requested.lock().unwrap().clone();
+            if let Some(reason) = requested {
+                return reason;
+            }
+
+            notified.await;
+        }
+    }
+
+    // App request stamped with the send time and the client id of this install
+    pub fn app_request(&self) -> AppRequest {
+        AppRequest {
+            timestamp_ns: identity::timestamp_ns(),
+            user_id: self.client_id.clone(),
+        }
+    }
+
+    fn set_status(&self, status: ConnectionStatus) {
+        self.status.send_if_modified(|current| {
+            let changed = *current != status;
+            *current = status;
+            changed
+        });
+    }
+
+    pub fn status(&self) -> ConnectionStatus {
+        self.status.borrow().clone()
+    }
+
+    // Current connection, if there is one
+    pub async fn connection(&self) -> Result<Arc<ServiceConnection>, CommandError> {
+        self.connection
+            .read()
+            .await
+            .clone()
+            .ok_or(CommandError::NotConnected)
+    }
+
+    // Wait until there is a live connection to services
+    pub async fn connected(&self) -> Arc<ServiceConnection> {
+        let mut status = self.status.subscribe();
+        loop {
+            if let Ok(connection) = self.connection().await {
+                return connection;
+            }
+
+            // The sender lives as long as the link so this can't fail
+            let _ = status.changed().await;
+        }
+    }
+
+    // Services stopped answering even though the transport is up. If they still aren't answering
+    // the next time around the connection may be stale, so it is rebuilt
+    pub fn set_degraded(&self, reason: &str) {
+        let status = self.status();
+        if !matches!(
+            status.state,
+            ConnectionState::Connected | ConnectionState::Degraded
+        ) {
+            return;
+        }
+
+        if self.unresponsive.swap(true, Ordering::SeqCst) {
+            self.request_reconnect(reason.to_owned());
+        } else {
+            self.set_status(ConnectionStatus {
+                state: ConnectionState::Degraded,
+                attempt: status.attempt,
+                error: Some(reason.to_owned()),
+                retry_in_ms: None,
+            });
+        }
+    }
+
+    // Services are answering again
+    pub fn set_healthy(&self) {
+        self.answered.store(true, Ordering::SeqCst);
+        self.unresponsive.store(false, Ordering::SeqCst);
+
+        let status = self.status();
+        if status.state == ConnectionState::Degraded
+            && self.publish_failures.load(Ordering::SeqCst) == 0
+        {
+            self.set_status(ConnectionStatus::new(
+                ConnectionState::Connected,
+                status.attempt,
+            ));
+        }
+    }
+
+    // Track the outcome of a publish. A failure degrades the connection and too many in a row
+    // forces a reconnect
+    pub fn report(
+        &self,
+        request: &'static str,
+        result: Result<(), NodeError>,
+    ) -> Result<(), CommandError> {
+        match result {
+            Ok(()) => {
+                // Only clears what publish failures degraded, not an unresponsive app service
+                if self.publish_failures.swap(0, Ordering::SeqCst) > 0
+                    && !self.unresponsive.load(Ordering::SeqCst)
+                {
+                    let attempt = self.status().attempt;
+                    self.set_status(ConnectionStatus::new(ConnectionState::Connected, attempt));
+                }
+                Ok(())
+            }
+            Err(e) => {
+                let failures = self.publish_failures.fetch_add(1, Ordering::SeqCst) + 1;
+
+                if failures == MAX_PUBLISH_FAILURES {
+                    self.request_reconnect(e.to_string());
+                } else {
+                    self.set_status(ConnectionStatus {
+                        state: ConnectionState::Degraded,
+                        attempt: self.status().attempt,
+                        error: Some(e.to_string()),
+                        retry_in_ms: None,
+                    });
+                }
+
+                Err(e).context(PublishSnafu { request })
+            }
+        }
+    }
+}
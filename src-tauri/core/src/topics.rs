@@ -0,0 +1,95 @@
+use std::collections::HashMap;
+
+// Every topic the app publishes to or subscribes from on the service node. The key is what goes
+// in the topics section of the service config, the default name is used when there is no mapping
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Topic {
+    AppRequest,
+    AppResponse,
+    RunYaml,
+    ChartRequest,
+    OverallRequest,
+    ReadFromDirRequest,
+    ReadFromDirResponse,
+    StrategyFromLogRequest,
+    OverallFromLogRequest,
+}
+
+impl Topic {
+    pub const ALL: [Topic; 9] = [
+        Topic::AppRequest,
+        Topic::AppResponse,
+        Topic::RunYaml,
+        Topic::ChartRequest,
+        Topic::OverallRequest,
+        Topic::ReadFromDirRequest,
+        Topic::ReadFromDirResponse,
+        Topic::StrategyFromLogRequest,
+        Topic::OverallFromLogRequest,
+    ];
+
+    pub fn key(&self) -> &'static str {
+        match self {
+            Topic::AppRequest => "app_request",
+            Topic::AppResponse => "app_response",
+            Topic::RunYaml => "run_yaml",
+            Topic::ChartRequest => "chart_request",
+            Topic::OverallRequest => "overall_request",
+            Topic::ReadFromDirRequest => "read_from_dir_request",
+            Topic::ReadFromDirResponse => "read_from_dir_response",
+            Topic::StrategyFromLogRequest => "strategy_from_log_request",
+            Topic::OverallFromLogRequest => "overall_from_log_request",
+        }
+    }
+
+    pub fn default_name(&self) -> &'static str {
+        // Default topic names match the config keys
+        self.key()
+    }
+
+    pub fn from_key(key: &str) -> Option<Topic> {
+        Topic::ALL.into_iter().find(|topic| topic.key() == key)
+    }
+}
+
+// Resolved topic names for every Topic, using the service config mapping when there is one
+#[derive(Debug, Clone)]
+pub struct TopicConfig {
+    topics: HashMap<Topic, String>,
+}
+
+impl TopicConfig {
+    // Build from the topics section of the service config. Returns the keys that don't match a Topic
+    pub fn from_config(mapping: &HashMap<String, String>) -> (Self, Vec<String>) {
+        let mut topic_config = Self::default();
+        let mut unknown = Vec::new();
+
+        for (key, name) in mapping {
+            match Topic::from_key(key) {
+                Some(topic) => {
+                    topic_config.topics.insert(topic, name.clone());
+                }
+                None => unknown.push(key.clone()),
+            }
+        }
+
+        unknown.sort();
+        (topic_config, unknown)
+    }
+
+    // Mapping of topic name from config. If no mapping exists then this returns default topic
+    pub fn get(&self, topic: Topic) -> &str {
+        &self.topics[&topic]
+    }
+}
+
+impl Default for TopicConfig {
+    fn default() -> Self {
+        Self {
+            topics: Topic::ALL
+                .into_iter()
+                .map(|topic| (topic, topic.default_name().to_owned()))
+                .collect(),
+        }
+    }
+}
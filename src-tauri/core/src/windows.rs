@@ -0,0 +1,203 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Mutex;
+use tradebot_protos::messages::enums::MessageType;
+
+// The batch, strategy, symbol and period a window shows
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WindowScope {
+    pub batch_id: String,
+    pub strategy_id: String,
+    pub symbol: String,
+    pub period_s: u32,
+}
+
+// Label prefix of windows opened with open_strategy_window, the frontend checks for it too
+pub const STRATEGY_WINDOW_PREFIX: &str = "strategy-";
+
+// Where a forwarded event goes
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Target {
+    All,
+    Windows(Vec<String>),
+}
+
+// Message types that belong to the strategy being charted. Everything else, like overall stats for
+// a batch, goes to every window
+pub fn strategy_scoped(mtype: MessageType) -> bool {
+    matches!(
+        mtype,
+        MessageType::Chart
+            | MessageType::AlgoChart
+            | MessageType::Candle
+            | MessageType::Point
+            | MessageType::Advice
+            | MessageType::Rectangle
+            | MessageType::Order
+            | MessageType::OrderFilled
+            | MessageType::OrderList
+            | MessageType::PositionStats
+            | MessageType::TotalPnl
+            | MessageType::TotalPnlRealized
+            | MessageType::TotalPnlUnrealized
+            | MessageType::PositionPnlRealized
+            | MessageType::PositionPnlRealizedList
+            | MessageType::PositionPnlUnrealized
+    )
+}
+
+// Routes strategy streams only to the windows showing that strategy. App feed messages don't say
+// which strategy they belong to, they are for whatever was last asked for with a chart request on
+// their feed, so that is what gets matched against the scope each window declared. Windows that
+// haven't declared a scope get everything. A feed streams one strategy at a time, so windows showing
+// another strategy of the same feed stop getting updates once a chart request replaces theirs
+#[derive(Default)]
+pub struct WindowRouter {
+    scopes: Mutex<HashMap<String, WindowScope>>,
+    // Scope streamed on each app feed, by feed id
+    streaming: Mutex<HashMap<String, WindowScope>>,
+    strategy_windows: AtomicU32,
+}
+
+impl WindowRouter {
+    pub fn new() -> Self {
+        Self {
+            scopes: Mutex::new(HashMap::new()),
+            streaming: Mutex::new(HashMap::new()),
+            strategy_windows: AtomicU32::new(0),
+        }
+    }
+
+    // Label for a new detached strategy window. Strategy ids can hold characters window labels
+    // can't, so windows are just numbered
+    pub fn next_strategy_window(&self) -> String {
+        let n = self.strategy_windows.fetch_add(1, Ordering::Relaxed) + 1;
+        format!("{}{}", STRATEGY_WINDOW_PREFIX, n)
+    }
+
+    // Detached window already showing the scope, if any
+    pub fn strategy_window(&self, scope: &WindowScope) -> Option<String> {
+        self.scopes
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|(label, s)| label.starts_with(STRATEGY_WINDOW_PREFIX) && *s == scope)
+            .map(|(label, _)| label.clone())
+    }
+
+    pub fn set_scope(&self, label: &str, scope: WindowScope) {
+        self.scopes.lock().unwrap().insert(label.to_owned(), scope);
+    }
+
+    // Window went back to showing nothing in particular, or was closed
+    pub fn clear_scope(&self, label: &str) {
+        self.scopes.lock().unwrap().remove(label);
+    }
+
+    pub fn scope(&self, label: &str) -> Option<WindowScope> {
+        self.scopes.lock().unwrap().get(label).cloned()
+    }
+
+    // Services now stream the given scope on the app feeds. Returns the scopes that were streamed on
+    // them before and aren't anymore
+    pub fn streaming(&self, feed_ids: &[String], scope: Option<WindowScope>) -> Vec<WindowScope> {
+        let mut streaming = self.streaming.lock().unwrap();
+        let mut replaced = Vec::new();
+        for feed_id in feed_ids {
+            let previous = match &scope {
+                Some(scope) => streaming.insert(feed_id.clone(), scope.clone()),
+                None => streaming.remove(feed_id),
+            };
+            if let Some(previous) = previous {
+                if Some(&previous) != scope.as_ref() && !replaced.contains(&previous) {
+                    replaced.push(previous);
+                }
+            }
+        }
+
+        replaced
+    }
+
+    // Windows showing the scope
+    pub fn showing(&self, scope: &WindowScope) -> Vec<String> {
+        self.scopes
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(_, s)| *s == scope)
+            .map(|(label, _)| label.clone())
+            .collect()
+    }
+
+    pub fn streamed(&self, feed_id: &str) -> Option<WindowScope> {
+        self.streaming.lock().unwrap().get(feed_id).cloned()
+    }
+
+    // Feed was closed, nothing is streamed on it anymore
+    pub fn forget_feed(&self, feed_id: &str) {
+        self.streaming.lock().unwrap().remove(feed_id);
+    }
+
+    // Windows a message for the streamed scope goes to
+    pub fn target(
+        &self,
+        mtype: MessageType,
+        streaming: Option<&WindowScope>,
+        windows: &[String],
+    ) -> Target {
+        if !strategy_scoped(mtype) {
+            return Target::All;
+        }
+
+        let scopes = self.scopes.lock().unwrap();
+        if scopes.is_empty() {
+            return Target::All;
+        }
+
+        Target::Windows(
+            windows
+                .iter()
+                .filter(|label| match scopes.get(*label) {
+                    Some(scope) => streaming == Some(scope),
+                    None => true,
+                })
+                .cloned()
+                .collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scope(strategy_id: &str) -> WindowScope {
+        WindowScope {
+            batch_id: "live".to_owned(),
+            strategy_id: strategy_id.to_owned(),
+            symbol: "SPY".to_owned(),
+            period_s: 60,
+        }
+    }
+
+    #[test]
+    fn second_chart_on_a_feed_leaves_the_first_window_stale() {
+        let router = WindowRouter::new();
+        let feeds = ["127.0.0.1:7447".to_owned()];
+        router.set_scope("main", scope("sma"));
+        router.set_scope("strategy-1", scope("ema"));
+
+        assert!(router.streaming(&feeds, Some(scope("sma"))).is_empty());
+        assert!(router.streaming(&feeds, Some(scope("sma"))).is_empty());
+
+        let replaced = router.streaming(&feeds, Some(scope("ema")));
+        assert_eq!(replaced, vec![scope("sma")]);
+        assert_eq!(router.showing(&replaced[0]), vec!["main".to_owned()]);
+
+        let windows = ["main".to_owned(), "strategy-1".to_owned()];
+        let target = router.target(MessageType::Candle, Some(&scope("ema")), &windows);
+        assert_eq!(target, Target::Windows(vec!["strategy-1".to_owned()]));
+    }
+}
@@ -0,0 +1,196 @@
+use serde_json::{json, Value};
+use snafu::OptionExt;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::api::cli::Matches;
+use tokio::sync::mpsc;
+use tradebot_app_core::config::ServiceConfig;
+use tradebot_app_core::error::{CommandError, InvalidArgumentSnafu};
+use tradebot_app_core::windows::Target;
+use tradebot_app_core::{AppCore, EventSink};
+
+// Things that can be done from the command line without a window
+#[derive(Debug, Clone, PartialEq)]
+pub enum CliCommand {
+    // Start a run of the yaml, like the run button
+    Run(PathBuf),
+    // Read the TradeBot logs in the directory, like the open logs button
+    OpenLogs(PathBuf),
+    // Strategies of every batch services advertise
+    ListStrategies,
+}
+
+impl CliCommand {
+    // Command for the subcommand given on the command line, if any
+    pub fn from_matches(matches: &Matches) -> Option<Result<Self, CommandError>> {
+        let subcommand = matches.subcommand.as_ref()?;
+        let path = |name: &'static str| {
+            subcommand
+                .matches
+                .args
+                .get(name)
+                .and_then(|arg| arg.value.as_str())
+                .with_context(|| InvalidArgumentSnafu {
+                    name,
+                    reason: "is required",
+                })
+                .and_then(|path| absolute(name, path))
+        };
+
+        match subcommand.name.as_str() {
+            "run" => Some(path("yaml").map(Self::Run)),
+            "open-logs" => Some(path("dir").map(Self::OpenLogs)),
+            "list-strategies" => Some(Ok(Self::ListStrategies)),
+            _ => None,
+        }
+    }
+}
+
+// Services may run somewhere else, relative paths mean nothing to them. The path is only joined onto
+// the working directory, it doesn't have to exist here and symlinks are kept
+fn absolute(name: &'static str, path: &str) -> Result<PathBuf, CommandError> {
+    let path = Path::new(path);
+    if path.is_absolute() {
+        return Ok(path.to_owned());
+    }
+
+    std::env::current_dir()
+        .map(|cwd| cwd.join(path))
+        .map_err(|e| CommandError::InvalidArgument {
+            name,
+            reason: format!("{}: {}", path.display(), e),
+        })
+}
+
+// Hands every event to the command waiting for its answer, connection problems go to stderr
+struct CliSink(mpsc::UnboundedSender<(String, Value)>);
+
+impl EventSink for CliSink {
+    fn emit(&self, event: &str, _target: &Target, payload: Value) {
+        if matches!(event, "connection_status" | "feed_error") {
+            eprintln!("{}: {}", event, payload);
+        }
+        let _ = self.0.send((event.to_owned(), payload));
+    }
+
+    fn windows(&self) -> Vec<String> {
+        Vec::new()
+    }
+}
+
+struct Answers {
+    events: mpsc::UnboundedReceiver<(String, Value)>,
+    timeout: Duration,
+}
+
+impl Answers {
+    // Payload of the next event with the name that is an answer to the request. Services get the
+    // request timeout to answer
+    async fn wait(
+        &mut self,
+        request: &'static str,
+        event: &str,
+        answers: impl Fn(&Value) -> bool,
+    ) -> Result<Value, CommandError> {
+        let timeout = tokio::time::timeout(self.timeout, async {
+            while let Some((name, payload)) = self.events.recv().await {
+                if name == event && answers(&payload) {
+                    return Some(payload);
+                }
+            }
+            None
+        });
+
+        match timeout.await {
+            Ok(Some(payload)) => Ok(payload),
+            _ => Err(CommandError::Timeout {
+                request,
+                timeout_ms: self.timeout.as_millis() as u64,
+            }),
+        }
+    }
+}
+
+// Run the command against services and return what they answered
+pub async fn run(
+    command: CliCommand,
+    service_config: ServiceConfig,
+    client_id: String,
+    recording_dir: Option<PathBuf>,
+) -> Result<Value, CommandError> {
+    let timeout = service_config.request_timeout();
+    let (sender, events) = mpsc::unbounded_channel();
+    let core = AppCore::new(
+        service_config,
+        client_id,
+        recording_dir,
+        Arc::new(CliSink(sender)),
+    );
+    tokio::spawn(core.clone().run());
+
+    tokio::time::timeout(timeout, core.connected())
+        .await
+        .map_err(|_| CommandError::NotConnected)?;
+
+    let mut answers = Answers { events, timeout };
+
+    match command {
+        // Services answer a run with an app response advertising its feed. Replies to heartbeats list
+        // feeds too, so the feeds already advertised are followed first and the run's feed is the
+        // one with a batch that wasn't among them
+        CliCommand::Run(yaml) => {
+            tokio::time::timeout(timeout, core.follow_advertised())
+                .await
+                .map_err(|_| CommandError::Timeout {
+                    request: "app request",
+                    timeout_ms: timeout.as_millis() as u64,
+                })??;
+            let known: HashSet<String> = core
+                .list_feeds()
+                .into_iter()
+                .flat_map(|feed| feed.batch_ids)
+                .collect();
+            let is_new = |feed: &Value| {
+                feed["batch_ids"].as_array().is_some_and(|ids| {
+                    ids.iter()
+                        .any(|id| !known.contains(id.as_str().unwrap_or_default()))
+                })
+            };
+
+            let yaml_path = core.run_yaml(&yaml).await?;
+            let feeds = answers
+                .wait("run yaml", "feeds", |feeds| {
+                    feeds
+                        .as_array()
+                        .is_some_and(|feeds| feeds.iter().any(is_new))
+                })
+                .await?;
+            let feeds: Vec<&Value> = feeds
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter(|feed| is_new(feed))
+                .collect();
+
+            Ok(json!({ "yaml_path": yaml_path, "feeds": feeds }))
+        }
+        // Only services reading the logs answer with these
+        CliCommand::OpenLogs(dir) => {
+            core.read_from_dir(&dir).await?;
+            let logged = answers
+                .wait("read from dir request", "open_log_successful", |_| true)
+                .await?;
+            Ok(logged["batches"].clone())
+        }
+        // Any strategy list lists every advertised batch, whichever app request it answered
+        CliCommand::ListStrategies => {
+            core.app_request().await?;
+            let strategy_list = answers
+                .wait("app request", "strategy_list", |_| true)
+                .await?;
+            Ok(strategy_list["batches"].clone())
+        }
+    }
+}
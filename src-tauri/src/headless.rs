@@ -0,0 +1,261 @@
+use serde::Serialize;
+use serde_json::Value;
+use snafu::OptionExt;
+use std::collections::HashMap;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use tauri::api::cli::Matches;
+use tokio::sync::watch;
+use tradebot_app_core::config::ServiceConfig;
+use tradebot_app_core::error::{CommandError, InvalidArgumentSnafu};
+use tradebot_app_core::session::Origin;
+use tradebot_app_core::subscriptions::SubscriptionRegistry;
+use tradebot_app_core::windows::{self, Target, WindowScope};
+use tradebot_app_core::{AppCore, EventSink};
+use tradebot_protos::messages::enums::MessageType;
+
+// There are no windows, the chart request is made on behalf of this label
+const HEADLESS_LABEL: &str = "headless";
+
+// Which app feed messages get printed, filters that aren't set let everything through
+#[derive(Debug, Clone, Default)]
+pub struct Filter {
+    pub mtypes: Vec<MessageType>,
+    pub batch_id: Option<String>,
+    // Only applies to strategy messages, stats for the whole batch still get through
+    pub strategy_id: Option<String>,
+}
+
+impl Filter {
+    // Messages the app can't tell the batch of are for one of the batches their feed advertises
+    fn matches(&self, mtype: MessageType, origin: &Origin, feed_batch_ids: &[String]) -> bool {
+        if !self.mtypes.is_empty() && !self.mtypes.contains(&mtype) {
+            return false;
+        }
+        if let Some(batch_id) = &self.batch_id {
+            let matches = match &origin.batch_id {
+                Some(origin_batch_id) => origin_batch_id == batch_id,
+                None => feed_batch_ids.contains(batch_id),
+            };
+            if !matches {
+                return false;
+            }
+        }
+
+        !windows::strategy_scoped(mtype)
+            || self.strategy_id.is_none()
+            || self.strategy_id == origin.strategy_id
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct HeadlessOptions {
+    pub filter: Filter,
+    // Strategy to ask the chart for so its messages are streamed
+    pub chart: Option<WindowScope>,
+}
+
+impl HeadlessOptions {
+    // Options from the --mtype, --batch, --strategy, --symbol and --period arguments. Streaming a
+    // strategy needs all of batch, strategy, symbol and period. Strategy messages are only streamed
+    // for a charted strategy, so --strategy needs the chart too
+    pub fn from_matches(matches: &Matches) -> Result<Self, CommandError> {
+        let arg = |name| {
+            matches
+                .args
+                .get(name)
+                .and_then(|arg| arg.value.as_str())
+                .map(str::to_owned)
+        };
+
+        let mtypes = match matches.args.get("mtype").map(|arg| &arg.value) {
+            Some(Value::Array(names)) => names.iter().filter_map(Value::as_str).collect(),
+            Some(Value::String(name)) => vec![name.as_str()],
+            _ => Vec::new(),
+        };
+        let mtypes = mtypes
+            .into_iter()
+            .map(parse_mtype)
+            .collect::<Result<_, _>>()?;
+
+        let filter = Filter {
+            mtypes,
+            batch_id: arg("batch"),
+            strategy_id: arg("strategy"),
+        };
+
+        let chart = match (&filter.batch_id, &filter.strategy_id, arg("symbol")) {
+            (Some(batch_id), Some(strategy_id), Some(symbol)) => {
+                let period_s = arg("period")
+                    .and_then(|period| period.parse().ok())
+                    .filter(|period_s| *period_s > 0)
+                    .with_context(|| InvalidArgumentSnafu {
+                        name: "period",
+                        reason: "must be a number of seconds greater than 0",
+                    })?;
+
+                Some(WindowScope {
+                    batch_id: batch_id.clone(),
+                    strategy_id: strategy_id.clone(),
+                    symbol,
+                    period_s,
+                })
+            }
+            (_, _, Some(_)) => {
+                return InvalidArgumentSnafu {
+                    name: "symbol",
+                    reason: "needs --batch and --strategy",
+                }
+                .fail()
+            }
+            (_, Some(_), None) => {
+                return InvalidArgumentSnafu {
+                    name: "strategy",
+                    reason: "needs --batch, --symbol and --period",
+                }
+                .fail()
+            }
+            _ => None,
+        };
+
+        Ok(Self { filter, chart })
+    }
+}
+
+// Message type by name, case and separators don't matter so candle, Candle and order_filled work
+fn parse_mtype(name: &str) -> Result<MessageType, CommandError> {
+    let normalize = |name: &str| {
+        name.chars()
+            .filter(|c| c.is_alphanumeric())
+            .collect::<String>()
+            .to_lowercase()
+    };
+
+    let wanted = normalize(name);
+    SubscriptionRegistry::app()
+        .mtypes()
+        .into_iter()
+        .find(|mtype| normalize(&format!("{:?}", mtype)) == wanted)
+        .with_context(|| InvalidArgumentSnafu {
+            name: "mtype",
+            reason: format!("{} is not an app feed message type", name),
+        })
+}
+
+// One line of output
+#[derive(Serialize)]
+struct Line<'a> {
+    mtype: MessageType,
+    #[serde(flatten)]
+    origin: &'a Origin,
+    message: &'a Value,
+}
+
+// Prints app feed messages to stdout as JSON lines, connection problems go to stderr
+pub struct NdjsonSink {
+    filter: Filter,
+    // Number of app feeds being followed
+    feeds: watch::Sender<usize>,
+    // Batches each followed feed advertises, by feed id
+    feed_batch_ids: Mutex<HashMap<String, Vec<String>>>,
+}
+
+impl EventSink for NdjsonSink {
+    fn emit(&self, event: &str, _target: &Target, payload: Value) {
+        match event {
+            "feeds" => {
+                let feeds = payload.as_array().map(Vec::as_slice).unwrap_or_default();
+                *self.feed_batch_ids.lock().unwrap() = feeds
+                    .iter()
+                    .filter_map(|feed| {
+                        let id = feed["id"].as_str()?.to_owned();
+                        let batch_ids = feed["batch_ids"]
+                            .as_array()?
+                            .iter()
+                            .filter_map(|id| id.as_str().map(str::to_owned))
+                            .collect();
+                        Some((id, batch_ids))
+                    })
+                    .collect();
+                self.feeds.send_replace(feeds.len());
+            }
+            "connection_status" | "feed_error" | "feed_closed" => {
+                eprintln!("{}: {}", event, payload)
+            }
+            _ => (),
+        }
+    }
+
+    fn windows(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    fn message(&self, mtype: MessageType, origin: &Origin, payload: &Value) {
+        let matches = {
+            let feed_batch_ids = self.feed_batch_ids.lock().unwrap();
+            let batch_ids = origin
+                .feed_id
+                .as_ref()
+                .and_then(|feed_id| feed_batch_ids.get(feed_id));
+            self.filter
+                .matches(mtype, origin, batch_ids.map_or(&[], Vec::as_slice))
+        };
+        if !matches {
+            return;
+        }
+
+        let line = Line {
+            mtype,
+            origin,
+            message: payload,
+        };
+        let line = match serde_json::to_string(&line) {
+            Ok(line) => line,
+            Err(e) => {
+                eprintln!("Failed to serialize {:?}: {}", mtype, e);
+                return;
+            }
+        };
+
+        // Whatever reads stdout went away, like head or a closed pipe, so there is no one to print for
+        if writeln!(io::stdout().lock(), "{}", line).is_err() {
+            std::process::exit(0);
+        }
+    }
+}
+
+// Follow the app feeds without any windows, printing messages until the process is stopped
+pub async fn run(
+    service_config: ServiceConfig,
+    client_id: String,
+    recording_dir: Option<PathBuf>,
+    options: HeadlessOptions,
+) -> Result<(), CommandError> {
+    let (feeds, mut followed) = watch::channel(0);
+    let sink = NdjsonSink {
+        filter: options.filter,
+        feeds,
+        feed_batch_ids: Mutex::new(HashMap::new()),
+    };
+    let core = AppCore::new(service_config, client_id, recording_dir, Arc::new(sink));
+    let running = tokio::spawn(core.clone().run());
+
+    core.app_request().await?;
+
+    if let Some(scope) = options.chart {
+        // The chart comes back on the app feed, so it has to be followed before asking
+        let _ = followed.wait_for(|feeds| *feeds > 0).await;
+        core.chart_request(
+            HEADLESS_LABEL,
+            scope.batch_id,
+            scope.strategy_id,
+            scope.symbol,
+            scope.period_s,
+        )
+        .await?;
+    }
+
+    let _ = running.await;
+    Ok(())
+}
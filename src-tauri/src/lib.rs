@@ -0,0 +1,452 @@
+pub mod cli;
+pub mod headless;
+
+use serde_json::Value;
+use snafu::{OptionExt, ResultExt};
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::{AppHandle, Builder, Manager, RunEvent, Runtime, State, WindowBuilder, WindowUrl};
+use tradebot_app_core::config::{ServiceConfig, StartupError};
+use tradebot_app_core::error::{self, CommandError, DialogCancelledSnafu, OpenWindowSnafu};
+use tradebot_app_core::feeds::{FeedClosed, FeedInfo};
+use tradebot_app_core::forwarder::EventMetrics;
+use tradebot_app_core::health::ServiceHealth;
+use tradebot_app_core::recorder::{self, RecordingStatus};
+use tradebot_app_core::replay::ReplayStatus;
+use tradebot_app_core::subscriptions::AdvertisedTopic;
+use tradebot_app_core::supervisor::ConnectionStatus;
+use tradebot_app_core::windows::{Target, WindowScope};
+use tradebot_app_core::{AppCore, EventSink};
+
+// How long a detached strategy window gets to load before its chart is requested anyway
+const STRATEGY_WINDOW_READY_TIMEOUT: Duration = Duration::from_secs(10);
+
+// Errors that stopped the app from connecting to TradeBot services on startup, if any
+struct StartupState(Option<StartupError>);
+
+// Emits core events to the app windows
+pub struct TauriSink<R: Runtime>(pub AppHandle<R>);
+
+impl<R: Runtime> EventSink for TauriSink<R> {
+    fn emit(&self, event: &str, target: &Target, payload: Value) {
+        // Fails for windows that are closing, nothing to do about it but say so
+        match target {
+            Target::All => {
+                if let Err(e) = self.0.emit_all(event, payload) {
+                    eprintln!("Failed to emit {}: {}", event, e);
+                }
+            }
+            Target::Windows(labels) => {
+                for label in labels {
+                    if let Err(e) = self.0.emit_to(label, event, payload.clone()) {
+                        eprintln!("Failed to emit {} to window {}: {}", event, label, e);
+                    }
+                }
+            }
+        }
+    }
+
+    fn windows(&self) -> Vec<String> {
+        self.0.windows().into_keys().collect()
+    }
+}
+
+// The app with every command. The core is created once the app is set up, sending its events
+// through the sink new_sink makes for the app
+pub fn builder<R, F>(
+    builder: Builder<R>,
+    service_config: ServiceConfig,
+    client_id: String,
+    recording_dir: Option<PathBuf>,
+    new_sink: F,
+) -> Builder<R>
+where
+    R: Runtime,
+    F: FnOnce(AppHandle<R>) -> Arc<dyn EventSink> + Send + 'static,
+{
+    builder
+        .manage(StartupState(None))
+        .invoke_handler(tauri::generate_handler![
+            startup_error,
+            connection_status,
+            service_health,
+            topic_inventory,
+            forwarder_metrics,
+            set_window_scope,
+            clear_window_scope,
+            window_scope,
+            open_strategy_window,
+            list_feeds,
+            stop_feed,
+            get_session_snapshot,
+            start_recording,
+            stop_recording,
+            recording_status,
+            replay_open,
+            replay_play,
+            replay_pause,
+            replay_seek,
+            replay_speed,
+            replay_close,
+            replay_status,
+            app_request,
+            overall_request,
+            chart_request,
+            read_from_dir,
+            strategy_from_log_request,
+            overall_from_log_request,
+            run_yaml,
+        ])
+        .setup(move |app| {
+            let core = AppCore::new(
+                service_config,
+                client_id,
+                recording_dir,
+                new_sink(app.handle()),
+            );
+            app.manage(core.clone());
+            tauri::async_runtime::spawn(core.run());
+
+            Ok(())
+        })
+        .on_window_event(|event| {
+            if let tauri::WindowEvent::Destroyed = event.event() {
+                let core: State<Arc<AppCore>> = event.window().state();
+                core.router().clear_scope(event.window().label());
+            }
+        })
+}
+
+// Pass to App::run so the core gets to finish up before the process exits
+pub fn on_run_event<R: Runtime>(app_handle: &AppHandle<R>, event: RunEvent) {
+    if let RunEvent::Exit = event {
+        if let Some(core) = app_handle.try_state::<Arc<AppCore>>() {
+            core.shutdown();
+        }
+    }
+}
+
+// The app showing why it couldn't start, so the user can see what is wrong with the config
+pub fn startup_error_builder<R: Runtime>(builder: Builder<R>, error: StartupError) -> Builder<R> {
+    for e in &error.errors {
+        eprintln!("Service config {}: {}", error.config, e);
+    }
+
+    builder
+        .manage(StartupState(Some(error)))
+        .invoke_handler(tauri::generate_handler![startup_error])
+        .on_page_load(|window, _| {
+            let state: State<StartupState> = window.state();
+            if let Some(error) = &state.0 {
+                window.emit("startup_error", error).unwrap();
+            }
+        })
+}
+
+#[tauri::command]
+fn startup_error(state: tauri::State<'_, StartupState>) -> serde_json::Value {
+    serde_json::to_value(&state.0).unwrap_or_default()
+}
+
+#[tauri::command]
+fn connection_status(core: tauri::State<'_, Arc<AppCore>>) -> ConnectionStatus {
+    core.connection_status()
+}
+
+#[tauri::command]
+fn service_health(core: tauri::State<'_, Arc<AppCore>>) -> Vec<ServiceHealth> {
+    core.service_health()
+}
+
+#[tauri::command]
+fn topic_inventory(core: tauri::State<'_, Arc<AppCore>>) -> Vec<AdvertisedTopic> {
+    core.topic_inventory()
+}
+
+#[tauri::command]
+fn forwarder_metrics(core: tauri::State<'_, Arc<AppCore>>) -> HashMap<&'static str, EventMetrics> {
+    core.forwarder_metrics()
+}
+
+// Declare what the calling window shows so only matching strategy streams are sent to it
+#[tauri::command]
+fn set_window_scope<R: Runtime>(
+    scope: WindowScope,
+    core: tauri::State<'_, Arc<AppCore>>,
+    window: tauri::Window<R>,
+) -> Result<(), CommandError> {
+    core.set_window_scope(window.label(), scope)
+}
+
+// Calling window gets every stream again
+#[tauri::command]
+fn clear_window_scope<R: Runtime>(core: tauri::State<'_, Arc<AppCore>>, window: tauri::Window<R>) {
+    core.router().clear_scope(window.label());
+}
+
+// Scope the calling window was opened with, None for the main window
+#[tauri::command]
+fn window_scope<R: Runtime>(
+    core: tauri::State<'_, Arc<AppCore>>,
+    window: tauri::Window<R>,
+) -> Option<WindowScope> {
+    core.router().scope(window.label())
+}
+
+// Open a window showing only the given strategy and request its chart for it. If a window already
+// shows the strategy it is focused instead. Returns the window label
+#[tauri::command]
+async fn open_strategy_window<R: Runtime>(
+    batch_id: String,
+    strategy_id: String,
+    symbol: String,
+    period_s: u32,
+    from_log: bool,
+    core: tauri::State<'_, Arc<AppCore>>,
+    app_handle: AppHandle<R>,
+) -> Result<String, CommandError> {
+    error::require("strategy id", &strategy_id)?;
+    error::require("symbol", &symbol)?;
+    if period_s == 0 {
+        return error::InvalidArgumentSnafu {
+            name: "period",
+            reason: "must be greater than 0",
+        }
+        .fail();
+    }
+
+    let scope = WindowScope {
+        batch_id: batch_id.clone(),
+        strategy_id: strategy_id.clone(),
+        symbol: symbol.clone(),
+        period_s,
+    };
+
+    let router = core.router();
+    if let Some(label) = router.strategy_window(&scope) {
+        if let Some(window) = app_handle.get_window(&label) {
+            let _ = window.set_focus();
+            return Ok(label);
+        }
+    }
+
+    // Scope is set before the window exists so nothing meant for other strategies reaches it
+    let label = router.next_strategy_window();
+    router.set_scope(&label, scope);
+
+    // The window resumes its chart with the same kind of request it was opened with
+    let url = format!("index.html?fromLog={}", from_log);
+    let window = WindowBuilder::new(&app_handle, &label, WindowUrl::App(url.into()))
+        .title(format!("{} {} {}s", strategy_id, symbol, period_s))
+        .inner_size(1460.0, 1100.0)
+        .build();
+    let window = match window {
+        Ok(window) => window,
+        Err(e) => {
+            router.clear_scope(&label);
+            return Err(e.into()).context(OpenWindowSnafu { label });
+        }
+    };
+
+    // Wait for the frontend to listen before requesting, otherwise the chart would be emitted to a
+    // window that isn't showing anything yet
+    let (ready_tx, ready_rx) = tokio::sync::oneshot::channel();
+    window.once("strategy_window_ready", move |_| {
+        let _ = ready_tx.send(());
+    });
+    if tokio::time::timeout(STRATEGY_WINDOW_READY_TIMEOUT, ready_rx)
+        .await
+        .is_err()
+    {
+        eprintln!(
+            "Strategy window {} didn't report ready, requesting its chart anyway",
+            label
+        );
+    }
+
+    if from_log {
+        core.strategy_from_log_request(&label, batch_id, strategy_id, symbol, period_s)
+            .await?;
+    } else {
+        core.chart_request(&label, batch_id, strategy_id, symbol, period_s)
+            .await?;
+    }
+
+    Ok(label)
+}
+
+#[tauri::command]
+fn get_session_snapshot(
+    batch_id: String,
+    strategy_id: String,
+    symbol: String,
+    period_s: u32,
+    core: tauri::State<'_, Arc<AppCore>>,
+) -> Result<BTreeMap<&'static str, serde_json::Value>, CommandError> {
+    core.session_snapshot(batch_id, strategy_id, symbol, period_s)
+}
+
+#[tauri::command]
+fn start_recording(core: tauri::State<'_, Arc<AppCore>>) -> Result<RecordingStatus, CommandError> {
+    core.start_recording()
+}
+
+#[tauri::command]
+fn stop_recording(
+    core: tauri::State<'_, Arc<AppCore>>,
+) -> Result<Option<RecordingStatus>, CommandError> {
+    core.stop_recording()
+}
+
+#[tauri::command]
+fn recording_status(core: tauri::State<'_, Arc<AppCore>>) -> Option<RecordingStatus> {
+    core.recording_status()
+}
+
+// Asks for the file if no path is given
+#[tauri::command]
+async fn replay_open(
+    path: Option<String>,
+    core: tauri::State<'_, Arc<AppCore>>,
+) -> Result<ReplayStatus, CommandError> {
+    use tauri::api::dialog::blocking::FileDialogBuilder;
+
+    let path = match path {
+        Some(path) => PathBuf::from(path),
+        None => FileDialogBuilder::new()
+            .add_filter("recording", &[recorder::EXTENSION])
+            .pick_file()
+            .context(DialogCancelledSnafu {
+                selection: "recording",
+            })?,
+    };
+
+    core.replay_open(&path)
+}
+
+#[tauri::command]
+fn replay_play(core: tauri::State<'_, Arc<AppCore>>) -> Result<ReplayStatus, CommandError> {
+    core.replay_play()
+}
+
+#[tauri::command]
+fn replay_pause(core: tauri::State<'_, Arc<AppCore>>) -> Result<ReplayStatus, CommandError> {
+    core.replay_pause()
+}
+
+#[tauri::command]
+fn replay_seek(
+    position_ns: u64,
+    core: tauri::State<'_, Arc<AppCore>>,
+) -> Result<ReplayStatus, CommandError> {
+    core.replay_seek(position_ns)
+}
+
+#[tauri::command]
+fn replay_speed(
+    speed: f64,
+    core: tauri::State<'_, Arc<AppCore>>,
+) -> Result<ReplayStatus, CommandError> {
+    core.replay_speed(speed)
+}
+
+// Stop replaying and go back to live requests
+#[tauri::command]
+fn replay_close(core: tauri::State<'_, Arc<AppCore>>) -> Result<ReplayStatus, CommandError> {
+    core.replay_close()
+}
+
+#[tauri::command]
+fn replay_status(core: tauri::State<'_, Arc<AppCore>>) -> Option<ReplayStatus> {
+    core.replay_status()
+}
+
+#[tauri::command]
+fn list_feeds(core: tauri::State<'_, Arc<AppCore>>) -> Vec<FeedInfo> {
+    core.list_feeds()
+}
+
+#[tauri::command]
+async fn stop_feed(
+    id: String,
+    core: tauri::State<'_, Arc<AppCore>>,
+) -> Result<FeedClosed, CommandError> {
+    core.stop_feed(id).await
+}
+
+#[tauri::command]
+async fn app_request(core: tauri::State<'_, Arc<AppCore>>) -> Result<(), CommandError> {
+    core.app_request().await
+}
+
+#[tauri::command]
+async fn chart_request<R: Runtime>(
+    batch_id: String,
+    strategy_id: String,
+    symbol: String,
+    period_s: u32,
+    core: tauri::State<'_, Arc<AppCore>>,
+    window: tauri::Window<R>,
+) -> Result<(), CommandError> {
+    core.chart_request(window.label(), batch_id, strategy_id, symbol, period_s)
+        .await
+}
+
+#[tauri::command]
+async fn overall_request(
+    core: tauri::State<'_, Arc<AppCore>>,
+    batch_id: String,
+) -> Result<(), CommandError> {
+    core.overall_request(batch_id).await
+}
+
+#[tauri::command]
+async fn read_from_dir(core: tauri::State<'_, Arc<AppCore>>) -> Result<(), CommandError> {
+    use tauri::api::dialog::blocking::FileDialogBuilder;
+
+    let dr = FileDialogBuilder::new()
+        .pick_folder()
+        .context(DialogCancelledSnafu {
+            selection: "log directory",
+        })?;
+
+    core.read_from_dir(&dr).await
+}
+
+#[tauri::command]
+async fn strategy_from_log_request<R: Runtime>(
+    batch_id: String,
+    strategy_id: String,
+    symbol: String,
+    period_s: u32,
+    core: tauri::State<'_, Arc<AppCore>>,
+    window: tauri::Window<R>,
+) -> Result<(), CommandError> {
+    core.strategy_from_log_request(window.label(), batch_id, strategy_id, symbol, period_s)
+        .await
+}
+
+#[tauri::command]
+async fn overall_from_log_request(
+    batch_id: String,
+    core: tauri::State<'_, Arc<AppCore>>,
+) -> Result<(), CommandError> {
+    core.overall_from_log_request(batch_id).await
+}
+
+// Returns the path of the yaml that was run
+#[tauri::command]
+async fn run_yaml(core: tauri::State<'_, Arc<AppCore>>) -> Result<String, CommandError> {
+    use tauri::api::dialog::blocking::FileDialogBuilder;
+
+    let dr = FileDialogBuilder::new()
+        .add_filter("yaml", &["yml"])
+        .pick_file()
+        .context(DialogCancelledSnafu {
+            selection: "strategy yaml",
+        })?;
+
+    core.run_yaml(&dr).await
+}
@@ -0,0 +1,126 @@
+// Runs the command line subcommands against an in-process mock of TradeBot services
+mod common;
+
+use serde_json::Value;
+use std::path::PathBuf;
+use tauri::api::cli::{ArgData, Matches, SubcommandMatches};
+use tradebot_app_core::config::ServiceConfig;
+use tradebot_app_core::error::CommandError;
+use tradebot_app_core::mock::{MockBackend, Received};
+use tradebot_app_lib::cli::{self, CliCommand};
+
+async fn start(port: u16) -> (ServiceConfig, MockBackend) {
+    let service_config = common::service_config(port);
+    let backend = MockBackend::start(
+        &service_config,
+        common::feed(&service_config),
+        common::script(),
+    )
+    .await
+    .expect("mock backend failed to start");
+
+    (service_config, backend)
+}
+
+async fn run(command: CliCommand, service_config: ServiceConfig) -> Value {
+    let recording_dir =
+        Some(std::env::temp_dir().join(format!("tradebot-app-test-{}", service_config.port)));
+    cli::run(
+        command,
+        service_config,
+        "test-client".to_owned(),
+        recording_dir,
+    )
+    .await
+    .expect("command failed")
+}
+
+// Matches for the subcommand with the given arguments, like tauri parses them
+fn subcommand(name: &str, args: &[(&str, &str)]) -> Matches {
+    let mut sub_matches = Matches::default();
+    for (arg, value) in args {
+        let mut data = ArgData::default();
+        data.value = Value::String(value.to_string());
+        data.occurrences = 1;
+        sub_matches.args.insert(arg.to_string(), data);
+    }
+
+    let mut subcommand = SubcommandMatches::default();
+    subcommand.name = name.to_owned();
+    subcommand.matches = sub_matches;
+
+    let mut matches = Matches::default();
+    matches.subcommand = Some(Box::new(subcommand));
+    matches
+}
+
+#[test]
+fn subcommands_from_matches() {
+    assert!(CliCommand::from_matches(&Matches::default()).is_none());
+
+    let command = CliCommand::from_matches(&subcommand("list-strategies", &[]));
+    assert_eq!(command.unwrap().unwrap(), CliCommand::ListStrategies);
+
+    // Relative paths are made absolute without having to exist here
+    let command = CliCommand::from_matches(&subcommand("run", &[("yaml", "yamls/sma.yml")]));
+    let cwd = std::env::current_dir().unwrap();
+    assert_eq!(
+        command.unwrap().unwrap(),
+        CliCommand::Run(cwd.join("yamls/sma.yml"))
+    );
+
+    let command = CliCommand::from_matches(&subcommand("open-logs", &[("dir", "/no/such/logs")]));
+    assert_eq!(
+        command.unwrap().unwrap(),
+        CliCommand::OpenLogs(PathBuf::from("/no/such/logs"))
+    );
+
+    let command = CliCommand::from_matches(&subcommand("run", &[]));
+    assert!(matches!(
+        command.unwrap(),
+        Err(CommandError::InvalidArgument { name: "yaml", .. })
+    ));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn list_strategies_prints_advertised_batches() {
+    let (service_config, _backend) = start(17471).await;
+
+    let batches = run(CliCommand::ListStrategies, service_config).await;
+    assert_eq!(batches[0]["batch_id"], "live");
+    assert_eq!(batches[0]["strategies"][0]["strategy_id"], "sma");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn open_logs_prints_logged_batches() {
+    let (service_config, backend) = start(17481).await;
+    let dir = std::env::temp_dir();
+
+    let batches = run(CliCommand::OpenLogs(dir.clone()), service_config).await;
+    assert_eq!(batches[0]["batch_id"], "from-log");
+    assert!(backend.received().iter().any(|r| matches!(
+        r,
+        Received::ReadFromDir(request) if request.log_dir == dir.display().to_string()
+    )));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn run_prints_the_feed_of_the_run() {
+    let (service_config, backend) = start(17501).await;
+    let yaml = std::env::temp_dir().join("sma.yml");
+
+    // Heartbeat replies advertise the live batch on the same feed, only the run's batch counts
+    let answer = run(CliCommand::Run(yaml.clone()), service_config).await;
+    assert_eq!(answer["yaml_path"], yaml.display().to_string());
+    let feeds = answer["feeds"].as_array().unwrap();
+    assert_eq!(feeds.len(), 1);
+    assert_eq!(feeds[0]["id"], "127.0.0.1:17502");
+    assert!(feeds[0]["batch_ids"]
+        .as_array()
+        .unwrap()
+        .contains(&Value::from("run")));
+    assert!(backend.received().iter().any(|r| matches!(
+        r,
+        Received::RunYaml(request) if request.yaml_path == yaml.display().to_string()
+    )));
+}
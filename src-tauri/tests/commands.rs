@@ -0,0 +1,269 @@
+// Drives the Tauri commands on the mock runtime against an in-process mock of TradeBot services
+mod common;
+
+use serde_json::{json, Value};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::api::ipc::CallbackFn;
+use tauri::test::{get_ipc_response, mock_builder, mock_context, noop_assets, MockRuntime};
+use tauri::{App, InvokePayload, Manager, Window};
+use tradebot_app_core::mock::{MockBackend, Received};
+use tradebot_app_core::windows::Target;
+use tradebot_app_core::{AppCore, EventSink};
+use tradebot_app_lib::TauriSink;
+
+// Peers on localhost need a moment to find each other, anything slower than this is a failure
+const EVENT_TIMEOUT: Duration = Duration::from_secs(10);
+
+// Emits to the windows like the app does and also hands every event to the test
+struct TestSink {
+    tauri: TauriSink<MockRuntime>,
+    events: Mutex<Sender<(String, Value)>>,
+}
+
+impl EventSink for TestSink {
+    fn emit(&self, event: &str, target: &Target, payload: Value) {
+        self.tauri.emit(event, target, payload.clone());
+        let _ = self
+            .events
+            .lock()
+            .unwrap()
+            .send((event.to_owned(), payload));
+    }
+
+    fn windows(&self) -> Vec<String> {
+        self.tauri.windows()
+    }
+}
+
+struct TestApp {
+    app: App<MockRuntime>,
+    window: Window<MockRuntime>,
+    events: Receiver<(String, Value)>,
+    backend: MockBackend,
+}
+
+impl TestApp {
+    // App and mock backend talking on the given port, the app feed is on the port after it. Every
+    // test uses its own ports so tests can run in parallel
+    fn start(port: u16) -> Self {
+        let service_config = common::service_config(port);
+        let feed = common::feed(&service_config);
+
+        let backend = tauri::async_runtime::block_on(MockBackend::start(
+            &service_config,
+            feed,
+            common::script(),
+        ))
+        .expect("mock backend failed to start");
+
+        let (sender, events) = mpsc::channel();
+        let recording_dir = Some(std::env::temp_dir().join(format!("tradebot-app-test-{}", port)));
+        let app = tradebot_app_lib::builder(
+            mock_builder(),
+            service_config,
+            "test-client".to_owned(),
+            recording_dir,
+            move |app_handle| {
+                Arc::new(TestSink {
+                    tauri: TauriSink(app_handle),
+                    events: Mutex::new(sender),
+                })
+            },
+        )
+        .build(mock_context(noop_assets()))
+        .expect("failed to build app");
+        let window = app.get_window("main").unwrap();
+
+        Self {
+            app,
+            window,
+            events,
+            backend,
+        }
+    }
+
+    fn invoke(&self, cmd: &str, args: Value) -> Result<Value, Value> {
+        get_ipc_response(
+            &self.window,
+            InvokePayload {
+                cmd: cmd.into(),
+                tauri_module: None,
+                callback: CallbackFn(0),
+                error: CallbackFn(1),
+                inner: args,
+                invoke_key: Some(tauri::test::INVOKE_KEY.into()),
+            },
+        )
+    }
+
+    // Payload of the next event with the name, skipping everything before it
+    fn event(&self, name: &str) -> Value {
+        let deadline = Instant::now() + EVENT_TIMEOUT;
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            match self.events.recv_timeout(remaining) {
+                Ok((event, payload)) if event == name => return payload,
+                Ok(_) => (),
+                Err(_) => panic!("No {} event within {:?}", name, EVENT_TIMEOUT),
+            }
+        }
+    }
+
+    // Ask for the app response and wait until its feed is followed
+    fn follow_feed(&self) -> Value {
+        assert_eq!(self.invoke("app_request", json!({})), Ok(Value::Null));
+        self.event("feeds")
+    }
+}
+
+#[test]
+fn app_request_follows_advertised_feed() {
+    let app = TestApp::start(17411);
+
+    let feeds = app.follow_feed();
+    assert_eq!(feeds.as_array().unwrap().len(), 1);
+    assert_eq!(feeds[0]["id"], "127.0.0.1:17412");
+    assert_eq!(feeds[0]["batch_ids"], json!(["live"]));
+
+    let strategy_list = app.event("strategy_list");
+    assert_eq!(strategy_list["batches"][0]["batch_id"], "live");
+
+    let inventory = app.invoke("topic_inventory", json!({})).unwrap();
+    let inventory = inventory.as_array().unwrap();
+    assert_eq!(inventory.len(), 4);
+    assert!(inventory
+        .iter()
+        .all(|topic| topic["status"] == "subscribed" && topic["feed_id"] == "127.0.0.1:17412"));
+
+    assert!(app
+        .backend
+        .received()
+        .iter()
+        .any(|r| matches!(r, Received::App(request) if request.user_id == "test-client")));
+}
+
+#[test]
+fn chart_request_resolves_once_chart_is_back() {
+    let app = TestApp::start(17421);
+    app.follow_feed();
+
+    let args = json!({ "batchId": "live", "strategyId": "sma", "symbol": "ES", "periodS": 60 });
+    assert_eq!(app.invoke("chart_request", args), Ok(Value::Null));
+    app.event("chart");
+
+    // Candles are batched per frame, orders go out one by one
+    let mut candles = 0;
+    while candles < 3 {
+        candles += app.event("update_candle").as_array().unwrap().len();
+    }
+    assert_eq!(app.event("order")["order_id"], 7);
+
+    assert!(app.backend.received().iter().any(|r| matches!(
+        r,
+        Received::Chart(request) if request.strategy_id == "sma" && request.period_s == 60
+    )));
+
+    // The requesting window shows the chart from now on
+    let scope = app.invoke("window_scope", json!({})).unwrap();
+    assert_eq!(scope["strategyId"], "sma");
+
+    let args = json!({ "batchId": "live", "strategyId": "sma", "symbol": "ES", "periodS": 60 });
+    let snapshot = app.invoke("get_session_snapshot", args).unwrap();
+    assert!(snapshot.get("chart").is_some());
+    assert_eq!(snapshot["update_candle"].as_array().unwrap().len(), 3);
+}
+
+#[test]
+fn overall_request_resolves_once_stats_are_back() {
+    let app = TestApp::start(17431);
+    app.follow_feed();
+
+    assert_eq!(
+        app.invoke("overall_request", json!({ "batchId": "live" })),
+        Ok(Value::Null)
+    );
+    app.event("overall_stats");
+
+    assert!(app
+        .backend
+        .received()
+        .iter()
+        .any(|r| matches!(r, Received::Overall(request) if request.batch_id == "live")));
+}
+
+#[test]
+fn read_from_dir_lists_logged_strategies() {
+    let app = TestApp::start(17441);
+    app.follow_feed();
+
+    // The command asks for the directory with a dialog, so go through the core behind it
+    let core = app.app.state::<Arc<AppCore>>().inner().clone();
+    tauri::async_runtime::block_on(core.read_from_dir("/logs".as_ref())).unwrap();
+
+    let logged = app.event("open_log_successful");
+    assert_eq!(logged["batches"][0]["batch_id"], "from-log");
+    assert!(app.backend.received().iter().any(|r| matches!(
+        r,
+        Received::ReadFromDir(request) if request.log_dir == "/logs"
+    )));
+}
+
+#[test]
+fn stop_feed_closes_it() {
+    let app = TestApp::start(17451);
+    app.follow_feed();
+
+    let closed = app
+        .invoke("stop_feed", json!({ "id": "127.0.0.1:17452" }))
+        .unwrap();
+    assert_eq!(closed["reason"], "stopped");
+    assert_eq!(closed["finished"], true);
+    assert_eq!(app.invoke("list_feeds", json!({})), Ok(json!([])));
+
+    let error = app
+        .invoke("stop_feed", json!({ "id": "127.0.0.1:17452" }))
+        .unwrap_err();
+    assert_eq!(error["kind"], "unknown_feed");
+}
+
+#[test]
+fn invalid_arguments_are_rejected() {
+    let app = TestApp::start(17461);
+
+    let error = app
+        .invoke("overall_request", json!({ "batchId": "" }))
+        .unwrap_err();
+    assert_eq!(error["kind"], "invalid_argument");
+
+    let args = json!({ "batchId": "live", "strategyId": "sma", "symbol": "", "periodS": 60 });
+    let error = app.invoke("strategy_from_log_request", args).unwrap_err();
+    assert_eq!(error["kind"], "invalid_argument");
+
+    let error = app
+        .invoke("replay_speed", json!({ "speed": 0.0 }))
+        .unwrap_err();
+    assert_eq!(error["kind"], "invalid_argument");
+
+    let error = app.invoke("replay_close", json!({})).unwrap_err();
+    assert_eq!(error["kind"], "no_replay");
+    assert!(app
+        .backend
+        .received()
+        .iter()
+        .all(|r| matches!(r, Received::App(_))));
+}
+
+#[test]
+fn heartbeats_keep_the_feed() {
+    let app = TestApp::start(17491);
+    let feeds = app.follow_feed();
+
+    // Heartbeats go out every 200ms and the mock stamps every reply it sends
+    std::thread::sleep(Duration::from_secs(2));
+    let events: Vec<_> = app.events.try_iter().map(|(event, _)| event).collect();
+    assert!(!events.iter().any(|event| event == "feed_closed"));
+    assert!(!events.iter().any(|event| event == "feeds"));
+    assert_eq!(app.invoke("list_feeds", json!({})), Ok(feeds));
+}
@@ -0,0 +1,55 @@
+// Service config and mock script shared by the integration tests
+use std::net::SocketAddr;
+use tradebot_app_core::config::ServiceConfig;
+use tradebot_app_core::mock::MockScript;
+use tradebot_protos::messages::{Batch, Candle, Order, Strategy, SymbolPeriods};
+
+// Services on localhost at the given port. Every test uses its own ports so tests can run in
+// parallel
+pub fn service_config(port: u16) -> ServiceConfig {
+    ServiceConfig {
+        zenoh_config_path: None,
+        ip: "127.0.0.1".to_owned(),
+        port,
+        topics: None,
+        heartbeat_interval_ms: Some(200),
+        request_timeout_ms: Some(5000),
+        client_id: None,
+        frame_interval_ms: None,
+        recording_dir: None,
+    }
+}
+
+// App feed of the mock backend, on the port after the services
+pub fn feed(service_config: &ServiceConfig) -> SocketAddr {
+    SocketAddr::from(([127, 0, 0, 1], service_config.port + 1))
+}
+
+pub fn batch(batch_id: &str) -> Batch {
+    Batch {
+        batch_id: batch_id.to_owned(),
+        strategies: vec![Strategy {
+            strategy_id: "sma".to_owned(),
+            symbol_periods: vec![SymbolPeriods {
+                symbol: "ES".to_owned(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        }],
+        ..Default::default()
+    }
+}
+
+pub fn script() -> MockScript {
+    MockScript {
+        batches: vec![batch("live")],
+        run_batches: vec![batch("run")],
+        log_batches: vec![batch("from-log")],
+        candles: vec![Candle::default(); 3],
+        orders: vec![Order {
+            order_id: 7,
+            ..Default::default()
+        }],
+        ..Default::default()
+    }
+}
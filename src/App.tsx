@@ -17,10 +17,27 @@ import { PnlDayChart } from "./PnlDayChart";
 import { PnlTimeChart } from "./PnlTimeChart";
 import { OverallPnlChart } from "./OverallPnlChart";
 import { StrategyNav } from "./StrategyNav";
+import { StartupErrorScreen } from "./StartupErrorScreen";
+import { StrategyWindow } from "./StrategyWindow";
+import { appWindow } from "@tauri-apps/api/window";
 import { AppResponse } from "./pb/app_response";
-import { SelectedStrategy, Strategy, Batch } from "./types";
+import { SelectedStrategy, Strategy, Batch, StartupError, WindowScope } from "./types";
+import { logCommandError } from "./commandError";
 
 function App() {
+    // Windows opened with open_strategy_window only show their strategy
+    if (appWindow.label.startsWith("strategy-")) {
+        return (
+            <MantineProvider defaultColorScheme="dark">
+                <StrategyWindow />
+            </MantineProvider>
+        );
+    }
+
+    return <MainWindow />;
+}
+
+function MainWindow() {
     let [batchesMap, setBatchesMap] = useState<Map<string, Batch>>(new Map<string, Batch>());
     let [selectedStrategy, setSelectedStrategy] = React.useState<SelectedStrategy>({ batchId: "", strategyId: "" });
     let [selectedOverallBatch, setSelectedOverallBatch] = React.useState<string>("");
@@ -30,12 +47,25 @@ function App() {
     let [loading, handler] = useDisclosure();
     let [refresh, setRefresh] = useState<boolean>(false);
     let [view, setView] = useState<string>("strategy");
+    let [startupError, setStartupError] = useState<StartupError | null>(null);
+    let [replayActive, setReplayActive] = useState<boolean>(false);
 
     const positionStatsEvent = "position_stats";
     const overallStatsEvent = "overall_stats";
 
     useEffect(() => {
-        invoke("app_request");
+        // Bad service config means there is no connection to TradeBot, so show what went wrong instead
+        listen<StartupError>("startup_error", (event) => {
+            setStartupError(event.payload);
+        });
+
+        invoke<StartupError | null>("startup_error").then((error) => {
+            if (error !== null) {
+                setStartupError(error);
+            } else {
+                invoke("app_request").catch(logCommandError);
+            }
+        });
 
         handler.open();
         listen<AppResponse>("strategy_list", (event) => {
@@ -72,8 +102,34 @@ function App() {
         listen("loading", (_event) => {
             handler.open();
         });
+
+        listen("replay", (_event) => {
+            setReplayActive(true);
+        });
+
+        listen("replay_closed", (_event) => {
+            setReplayActive(false);
+        });
+
+        // Show whatever the replayed session was streaming at this point
+        listen<WindowScope | null>("replay_scope", (event) => {
+            if (event.payload !== null) {
+                setSelectedStrategy({ batchId: event.payload.batchId, strategyId: event.payload.strategyId });
+                setActiveSymbol(event.payload.symbol);
+                setActivePeriod(event.payload.periodS);
+                setView("strategy");
+            }
+        });
     }, []);
 
+    if (startupError !== null) {
+        return (
+            <MantineProvider defaultColorScheme="dark">
+                <StartupErrorScreen startupError={startupError} />
+            </MantineProvider>
+        );
+    }
+
     return (
         <MantineProvider defaultColorScheme="dark">
             <Toolbar
@@ -85,6 +141,7 @@ function App() {
                 setActivePeriod={setActivePeriod}
                 setActiveSymbol={setActiveSymbol}
                 loading={loading}
+                liveModeActive={liveModeActive}
                 setRefresh={setRefresh}
                 setSelectedOverallBatch={setSelectedOverallBatch}
                 setView={setView}
@@ -105,6 +162,7 @@ function App() {
                                 activeSymbol={activeSymbol}
                                 liveModeActive={liveModeActive}
                                 refresh={refresh}
+                                replayActive={replayActive}
                             />
                         </Paper>
                         <Flex direction="column" gap="xl">
@@ -3,7 +3,7 @@ import { listen } from "@tauri-apps/api/event";
 import { invoke } from "@tauri-apps/api/tauri";
 
 import { createChart, CrosshairMode, createSeriesMarkers, IChartApi, ISeriesApi, Time, ISeriesMarkersPluginApi, SeriesMarker, CandlestickData, UTCTimestamp, HistogramData, LineData, BoxOptions, LineStyle, IBox, ColorType } from "lightweight-charts";
-import { Group, Text } from "@mantine/core";
+import { Button, Group, Text } from "@mantine/core";
 import { Chart } from "./pb/chart";
 import { AlgoChart } from "./pb/algo_chart";
 import { Legend, LineVal, SelectedStrategy } from "./types";
@@ -11,6 +11,7 @@ import { Candle } from "./pb/candle";
 import { Point } from "./pb/point";
 import { Rectangle } from "./pb/rectangle";
 import { Advice } from "./pb/advice";
+import { logCommandError } from "./commandError";
 
 interface ChartProps {
     selectedStrategy: SelectedStrategy,
@@ -18,9 +19,13 @@ interface ChartProps {
     activePeriod: number | undefined,
     refresh: boolean,
     liveModeActive: boolean,
+    // Chart of a detached strategy window, requested by open_strategy_window instead
+    detached?: boolean,
+    // Chart comes from a recorded session, there is nothing to request
+    replayActive?: boolean,
 }
 
-export function ChartContainer({ selectedStrategy, activePeriod, activeSymbol, liveModeActive, refresh }: ChartProps) {
+export function ChartContainer({ selectedStrategy, activePeriod, activeSymbol, liveModeActive, refresh, detached, replayActive }: ChartProps) {
     const defaultRectColor = "#0ff";
     const chartContainerRef = useRef<HTMLDivElement>(null);
     const chart = useRef<IChartApi>(undefined);
@@ -35,55 +40,70 @@ export function ChartContainer({ selectedStrategy, activePeriod, activeSymbol, l
     let initialChartReceived = useRef(false);
     let initialAlgoReceived = useRef(false);
     let [legend, setLegend] = React.useState<Legend | null>(null);
+    // Another chart of the same app feed was asked for since, this one isn't updated anymore
+    let [stale, setStale] = React.useState<boolean>(false);
+    // A chart request is waiting for its answer
+    let [requesting, setRequesting] = React.useState<boolean>(false);
     let eastOffset = 14400;
 
+    // Initialize candlestick chart with datafeed chart data
+    const setChartData = (chartData: Chart) => {
+        let candles: CandlestickData<Time>[] = [];
+        let volume: HistogramData<Time>[] = [];
+
+        for (const ohlcv of chartData.ohlcv) {
+            candles.push({
+                time: ohlcv.timestamp_ns / 1000000000 - eastOffset as UTCTimestamp,
+                open: ohlcv.open,
+                high: ohlcv.high,
+                low: ohlcv.low,
+                close: ohlcv.close,
+            });
+
+            volume.push({
+                time: ohlcv.timestamp_ns / 1000000000 - eastOffset as UTCTimestamp,
+                value: ohlcv.volume,
+                color: ohlcv.open > ohlcv.close ? "#ff4976" : "#4bffb5",
+            });
+        }
+
+        candleSeries.current?.setData(candles);
+        volumeSeries.current?.setData(volume);
+
+        initialChartReceived.current = true;
+    };
+
     useEffect(() => {
         const chart_unlisten = listen<Chart>("chart", (event) => {
-            let candles: CandlestickData<Time>[] = [];
-            let volume: HistogramData<Time>[] = [];
-
-            // Initialize candlestick chart with datafeed chart data
-            for (const ohlcv of event.payload.ohlcv) {
-                candles.push({
-                    time: ohlcv.timestamp_ns / 1000000000 - eastOffset as UTCTimestamp,
-                    open: ohlcv.open,
-                    high: ohlcv.high,
-                    low: ohlcv.low,
-                    close: ohlcv.close,
-                });
-
-                volume.push({
-                    time: ohlcv.timestamp_ns / 1000000000 - eastOffset as UTCTimestamp,
-                    value: ohlcv.volume,
-                    color: ohlcv.open > ohlcv.close ? "#ff4976" : "#4bffb5",
-                });
-            }
-
-            candleSeries.current?.setData(candles);
-            volumeSeries.current?.setData(volume);
+            setChartData(event.payload);
+            setStale(false);
+        });
 
-            initialChartReceived.current = true;
+        const stale_unlisten = listen("chart_stale", (_event) => {
+            setStale(true);
         });
 
-        const update_candle_unlisten = listen<Candle>("update_candle", (event) => {
-            // New incoming candle so update chart
-            if (initialChartReceived.current) {
-                if (candleSeries.current !== undefined && event.payload.ohlcv !== undefined) {
-                    candleSeries.current.update({
-                        time: event.payload.ohlcv.timestamp_ns / 1000000000 - eastOffset as UTCTimestamp,
-                        open: event.payload.ohlcv.open,
-                        high: event.payload.ohlcv.high,
-                        low: event.payload.ohlcv.low,
-                        close: event.payload.ohlcv.close,
-                    });
-                }
+        const update_candle_unlisten = listen<Candle[]>("update_candle", (event) => {
+            for (const candle of event.payload) {
+                // New incoming candle so update chart
+                if (initialChartReceived.current) {
+                    if (candleSeries.current !== undefined && candle.ohlcv !== undefined) {
+                        candleSeries.current.update({
+                            time: candle.ohlcv.timestamp_ns / 1000000000 - eastOffset as UTCTimestamp,
+                            open: candle.ohlcv.open,
+                            high: candle.ohlcv.high,
+                            low: candle.ohlcv.low,
+                            close: candle.ohlcv.close,
+                        });
+                    }
 
-                if (volumeSeries.current !== undefined && event.payload.ohlcv !== undefined) {
-                    volumeSeries.current.update({
-                        time: event.payload.ohlcv.timestamp_ns / 1000000000 - eastOffset as UTCTimestamp,
-                        value: event.payload.ohlcv.volume,
-                        color: event.payload.ohlcv.open > event.payload.ohlcv.close ? "#ff4976" : "#4bffb5",
-                    });
+                    if (volumeSeries.current !== undefined && candle.ohlcv !== undefined) {
+                        volumeSeries.current.update({
+                            time: candle.ohlcv.timestamp_ns / 1000000000 - eastOffset as UTCTimestamp,
+                            value: candle.ohlcv.volume,
+                            color: candle.ohlcv.open > candle.ohlcv.close ? "#ff4976" : "#4bffb5",
+                        });
+                    }
                 }
             }
         });
@@ -188,38 +208,42 @@ export function ChartContainer({ selectedStrategy, activePeriod, activeSymbol, l
             }
         });
 
-        const point_unlisten = listen<Point>("point", (event) => {
-            if (initialAlgoReceived.current) {
-                if (lineSeries.current.has(event.payload.description)) {
-                    lineSeries.current.get(event.payload.description)!.update({
-                        time: event.payload.value!.timestamp_ns / 1000000000 - eastOffset as UTCTimestamp,
-                        value: event.payload.value!.value,
-                    });
+        const point_unlisten = listen<Point[]>("point", (event) => {
+            for (const point of event.payload) {
+                if (initialAlgoReceived.current) {
+                    if (lineSeries.current.has(point.description)) {
+                        lineSeries.current.get(point.description)!.update({
+                            time: point.value!.timestamp_ns / 1000000000 - eastOffset as UTCTimestamp,
+                            value: point.value!.value,
+                        });
+                    }
                 }
             }
         });
 
-        const rect_unlisten = listen<Rectangle>("rectangle", (event) => {
-            if (initialAlgoReceived.current) {
-                const box: BoxOptions = {
-                    lowPrice: event.payload.low_price,
-                    highPrice: event.payload.high_price,
-                    earlyTime: event.payload.early_timestamp_ns / 1000000000 - eastOffset,
-                    lateTime: event.payload.late_timestamp_ns / 1000000000 - eastOffset,
-                    borderColor: event.payload.color !== null && event.payload.color !== undefined ? event.payload.color : defaultRectColor,
-                    borderWidth: 1,
-                    fillColor: event.payload.color !== null && event.payload.color !== undefined ? event.payload.color : defaultRectColor,
-                    fillOpacity: 0.2,
-                    borderVisible: true,
-                    axisLabelVisible: false,
-                    title: "box",
-                    borderStyle: LineStyle.Solid,
-                    corners: [],
-                };
-                const createdBox = candleSeries.current?.createBox(box);
-
-                if (createdBox !== undefined) {
-                    currentBoxes.current.push(createdBox);
+        const rect_unlisten = listen<Rectangle[]>("rectangle", (event) => {
+            for (const rect of event.payload) {
+                if (initialAlgoReceived.current) {
+                    const box: BoxOptions = {
+                        lowPrice: rect.low_price,
+                        highPrice: rect.high_price,
+                        earlyTime: rect.early_timestamp_ns / 1000000000 - eastOffset,
+                        lateTime: rect.late_timestamp_ns / 1000000000 - eastOffset,
+                        borderColor: rect.color !== null && rect.color !== undefined ? rect.color : defaultRectColor,
+                        borderWidth: 1,
+                        fillColor: rect.color !== null && rect.color !== undefined ? rect.color : defaultRectColor,
+                        fillOpacity: 0.2,
+                        borderVisible: true,
+                        axisLabelVisible: false,
+                        title: "box",
+                        borderStyle: LineStyle.Solid,
+                        corners: [],
+                    };
+                    const createdBox = candleSeries.current?.createBox(box);
+
+                    if (createdBox !== undefined) {
+                        currentBoxes.current.push(createdBox);
+                    }
                 }
             }
         });
@@ -255,9 +279,23 @@ export function ChartContainer({ selectedStrategy, activePeriod, activeSymbol, l
             advice_unlisten.then((fn) => fn());
             update_candle_unlisten.then((fn) => fn());
             chart_unlisten.then((fn) => fn());
+            stale_unlisten.then((fn) => fn());
         };
     }, []);
 
+    // Ask for the chart of the selected strategy, live or read from its logs
+    const requestChart = () => {
+        setRequesting(true);
+        invoke(liveModeActive ? "chart_request" : "strategy_from_log_request", {
+            batchId: selectedStrategy.batchId,
+            strategyId: selectedStrategy.strategyId,
+            symbol: activeSymbol,
+            periodS: activePeriod,
+        })
+            .catch(logCommandError)
+            .finally(() => setRequesting(false));
+    };
+
     useEffect(
         function () {
             console.log(selectedStrategy);
@@ -374,20 +412,22 @@ export function ChartContainer({ selectedStrategy, activePeriod, activeSymbol, l
                     initialAlgoReceived.current = false;
                     initialChartReceived.current = false;
 
-                    if (liveModeActive) {
-                        invoke("chart_request", {
+                    if (replayActive) {
+                        // Replayed chart may have come before this chart existed, take it from the session
+                        invoke<Record<string, any>>("get_session_snapshot", {
                             batchId: selectedStrategy.batchId,
                             strategyId: selectedStrategy.strategyId,
                             symbol: activeSymbol,
                             periodS: activePeriod,
-                        });
+                        }).then((snapshot) => {
+                            if (snapshot.chart !== undefined) {
+                                setChartData(snapshot.chart);
+                            }
+                        }).catch(logCommandError);
+                    } else if (detached) {
+                        // open_strategy_window already asked for this chart
                     } else {
-                        invoke("strategy_from_log_request", {
-                            batchId: selectedStrategy.batchId,
-                            strategyId: selectedStrategy.strategyId,
-                            symbol: activeSymbol,
-                            periodS: activePeriod,
-                        });
+                        requestChart();
                     }
                 }
             }
@@ -422,6 +462,17 @@ export function ChartContainer({ selectedStrategy, activePeriod, activeSymbol, l
 
     return (
         <div>
+            {stale && !replayActive && (
+                <Group gap="xs">
+                    <Text c="orange" fz="xs">
+                        Not live, another chart of this feed is streaming
+                    </Text>
+                    {/* Ask for the chart again so it is streamed here instead of the chart that replaced it */}
+                    <Button size="compact-xs" variant="subtle" loading={requesting} onClick={requestChart}>
+                        Resume
+                    </Button>
+                </Group>
+            )}
             <div
                 ref={chartContainerRef}
                 className="chart-container"
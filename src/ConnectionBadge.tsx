@@ -0,0 +1,78 @@
+import React, { useEffect } from "react";
+import { Badge, Text, Tooltip } from "@mantine/core";
+import { listen } from "@tauri-apps/api/event";
+import { invoke } from "@tauri-apps/api/tauri";
+import { AdvertisedTopic, ConnectionStatus, ServiceHealth } from "./types";
+
+const stateColorMap = new Map<string, string>([
+    ["connecting", "yellow"],
+    ["connected", "green"],
+    ["degraded", "orange"],
+    ["disconnected", "red"],
+]);
+
+export function ConnectionBadge() {
+    let [status, setStatus] = React.useState<ConnectionStatus | null>(null);
+    let [health, setHealth] = React.useState<ServiceHealth[]>([]);
+    let [topics, setTopics] = React.useState<AdvertisedTopic[]>([]);
+
+    useEffect(() => {
+        invoke<ConnectionStatus>("connection_status").then(setStatus);
+        invoke<ServiceHealth[]>("service_health").then(setHealth);
+        invoke<AdvertisedTopic[]>("topic_inventory").then(setTopics);
+
+        const status_unlisten = listen<ConnectionStatus>("connection_status", (event) => {
+            setStatus(event.payload);
+        });
+
+        const health_unlisten = listen<ServiceHealth[]>("service_health", (event) => {
+            setHealth(event.payload);
+        });
+
+        const topics_unlisten = listen<AdvertisedTopic[]>("topic_inventory", (event) => {
+            setTopics(event.payload);
+        });
+
+        return () => {
+            status_unlisten.then((fn) => fn()); // Ensure proper cleanup of the listener
+            health_unlisten.then((fn) => fn()); // Ensure proper cleanup of the listener
+            topics_unlisten.then((fn) => fn()); // Ensure proper cleanup of the listener
+        };
+    }, []);
+
+    if (status === null) {
+        return null;
+    }
+
+    let label = status.error ?? "TradeBot services " + status.state;
+    if (status.retry_in_ms !== null) {
+        label += ", retrying in " + (status.retry_in_ms / 1000).toFixed(1) + "s";
+    }
+
+    const healthLabel = (
+        <>
+            <Text size="sm">{label}</Text>
+            {health.map((service) => (
+                <Text size="xs" key={service.service} c={service.alive ? "green" : "dimmed"}>
+                    {service.service}: {service.alive ? "alive" : "not seen"}
+                    {service.latency_ms !== null ? " (" + service.latency_ms + " ms)" : ""}
+                </Text>
+            ))}
+            {topics
+                .filter((topic) => topic.status !== "subscribed")
+                .map((topic) => (
+                    <Text size="xs" key={topic.feed_id + topic.topic} c="orange">
+                        {topic.topic}: {topic.status === "failed" ? topic.error : "can't be shown yet (" + (topic.mtype ?? "type " + topic.mtype_id) + ")"}
+                    </Text>
+                ))}
+        </>
+    );
+
+    return (
+        <Tooltip label={healthLabel}>
+            <Badge color={stateColorMap.get(status.state)} variant="dot" size="lg" radius="0" style={{ marginLeft: "auto", alignSelf: "center" }}>
+                {status.state}
+            </Badge>
+        </Tooltip>
+    );
+}
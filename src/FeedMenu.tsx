@@ -0,0 +1,53 @@
+import React, { useEffect } from "react";
+import { ActionIcon, Menu } from "@mantine/core";
+import { IconPlugConnected } from "@tabler/icons-react";
+import { listen } from "@tauri-apps/api/event";
+import { invoke } from "@tauri-apps/api/tauri";
+import { FeedError, FeedInfo } from "./types";
+import { logCommandError } from "./commandError";
+
+// Active app feeds, clicking one stops it
+export function FeedMenu() {
+    let [feeds, setFeeds] = React.useState<FeedInfo[]>([]);
+
+    useEffect(() => {
+        invoke<FeedInfo[]>("list_feeds").then(setFeeds);
+
+        const feeds_unlisten = listen<FeedInfo[]>("feeds", (event) => {
+            setFeeds(event.payload);
+        });
+
+        const error_unlisten = listen<FeedError>("feed_error", (event) => {
+            console.error(event.payload.message);
+        });
+
+        return () => {
+            feeds_unlisten.then((fn) => fn()); // Ensure proper cleanup of the listener
+            error_unlisten.then((fn) => fn()); // Ensure proper cleanup of the listener
+        };
+    }, []);
+
+    return (
+        <Menu>
+            <Menu.Target>
+                <ActionIcon variant="filled" aria-label="Settings" size="xl" radius="0" color="#242424" disabled={feeds.length == 0}>
+                    <IconPlugConnected style={{ width: "70%", height: "70%" }} stroke={1.0} />
+                </ActionIcon>
+            </Menu.Target>
+
+            <Menu.Dropdown>
+                <Menu.Label>Stop feed</Menu.Label>
+                {feeds.map((feed) => (
+                    <Menu.Item
+                        key={feed.id}
+                        onClick={(e: any) => {
+                            invoke("stop_feed", { id: feed.id }).catch(logCommandError);
+                        }}
+                    >
+                        {feed.batch_ids.join(", ")} ({feed.id})
+                    </Menu.Item>
+                ))}
+            </Menu.Dropdown>
+        </Menu>
+    );
+}
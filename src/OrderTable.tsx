@@ -65,26 +65,28 @@ export function OrderTable({ selectedStrategy }: OrderTableProps) {
             setOrders(Array.from(orderTableData.current.values()).sort(compare));
         });
 
-        const pos_realized_unlisten = listen<PositionPnlRealized>("pos_pnl_realized", (event) => {
-            if (orderTableData.current.has(event.payload.position_id)) {
-                let orderTableEntry = orderTableData.current.get(event.payload.position_id);
-                orderTableEntry!.realized = event.payload.value!.value.toFixed(2);
-            } else {
-                orderTableData.current.set(event.payload.position_id, {
-                    id: "",
-                    fill_price: "",
-                    status: "",
-                    dt: "",
-                    side: "",
-                    amount: "",
-                    price: "",
-                    realized: event.payload.value!.value.toFixed(2),
-                    count: orderTableData.current.size + 1,
-                    timestamp_ns: 0,
-                });
-            }
+        const pos_realized_unlisten = listen<PositionPnlRealized[]>("pos_pnl_realized", (event) => {
+            for (const realized of event.payload) {
+                if (orderTableData.current.has(realized.position_id)) {
+                    let orderTableEntry = orderTableData.current.get(realized.position_id);
+                    orderTableEntry!.realized = realized.value!.value.toFixed(2);
+                } else {
+                    orderTableData.current.set(realized.position_id, {
+                        id: "",
+                        fill_price: "",
+                        status: "",
+                        dt: "",
+                        side: "",
+                        amount: "",
+                        price: "",
+                        realized: realized.value!.value.toFixed(2),
+                        count: orderTableData.current.size + 1,
+                        timestamp_ns: 0,
+                    });
+                }
 
-            setOrders(Array.from(orderTableData.current.values()).sort(compare));
+                setOrders(Array.from(orderTableData.current.values()).sort(compare));
+            }
         });
 
         const order_filled_unlisten = listen<OrderFilled>("order_filled", (event) => {
@@ -8,6 +8,7 @@ import { invoke } from "@tauri-apps/api/tauri";
 import { SelectedStrategy } from "./types";
 import { PnlCalendar as PnlCalendarPb } from "./pb/pnl_calendar";
 import { OverallDayStats } from "./pb/overall_day_stats";
+import { logCommandError } from "./commandError";
 
 interface PnlCalendarProps {
     selectedOverallBatch: string;
@@ -28,11 +29,11 @@ export function PnlCalendar({ selectedOverallBatch, setView, setSelectedStrategy
     useEffect(() => {
         if (selectedOverallBatch !== undefined && selectedOverallBatch.length > 0) {
             if (liveModeActive) {
-                invoke("overall_request", { batchId: selectedOverallBatch });
+                invoke("overall_request", { batchId: selectedOverallBatch }).catch(logCommandError);
             } else {
                 invoke("overall_from_log_request", {
                     batchId: selectedOverallBatch,
-                });
+                }).catch(logCommandError);
             }
         }
 
@@ -82,7 +83,7 @@ export function PnlCalendar({ selectedOverallBatch, setView, setSelectedStrategy
 
     useEffect(() => {
         if (selectedOverallBatch !== undefined && selectedOverallBatch.length > 0) {
-            invoke("overall_request", { batchId: selectedOverallBatch });
+            invoke("overall_request", { batchId: selectedOverallBatch }).catch(logCommandError);
         }
     }, [selectedOverallBatch]);
 
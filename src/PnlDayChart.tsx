@@ -6,6 +6,7 @@ import { invoke } from "@tauri-apps/api/tauri";
 import { Group, Paper, SimpleGrid, Text } from "@mantine/core";
 import { PnlCalendar } from "./pb/pnl_calendar";
 import { OverallDayStats } from "./pb/overall_day_stats";
+import { logCommandError } from "./commandError";
 
 interface PnlDayProps {
     selectedOverallBatch: string;
@@ -155,11 +156,11 @@ export function PnlDayChart({ selectedOverallBatch, liveModeActive }: PnlDayProp
 
         if (selectedOverallBatch !== undefined && selectedOverallBatch.length > 0) {
             if (liveModeActive) {
-                invoke("overall_request", { batchId: selectedOverallBatch });
+                invoke("overall_request", { batchId: selectedOverallBatch }).catch(logCommandError);
             } else {
                 invoke("overall_from_log_request", {
                     batchId: selectedOverallBatch,
-                });
+                }).catch(logCommandError);
             }
         }
 
@@ -6,6 +6,7 @@ import { invoke } from "@tauri-apps/api/tauri";
 import { Group, Paper, SimpleGrid, Text } from "@mantine/core";
 import { PnlHour } from "./pb/pnl_hour";
 import { OverallDayStats } from "./pb/overall_day_stats";
+import { logCommandError } from "./commandError";
 
 interface PnlTimeProps {
     selectedOverallBatch: string;
@@ -168,11 +169,11 @@ export function PnlTimeChart({ selectedOverallBatch, liveModeActive }: PnlTimePr
 
         if (selectedOverallBatch !== undefined && selectedOverallBatch.length > 0) {
             if (liveModeActive) {
-                invoke("overall_request", { batchId: selectedOverallBatch });
+                invoke("overall_request", { batchId: selectedOverallBatch }).catch(logCommandError);
             } else {
                 invoke("overall_from_log_request", {
                     batchId: selectedOverallBatch,
-                });
+                }).catch(logCommandError);
             }
         }
 
@@ -23,24 +23,28 @@ export function PositionPnlChart({ selectedStrategy }: PositionPnlChartProps) {
     let [pnl, setPnl] = useState<number>(0.0);
 
     useEffect(() => {
-        const realized_unlisten = listen<PositionPnlRealized>("pos_pnl_realized", (event) => {
-            setPnl(0.0);
-            positions.current.add(event.payload.position_id);
-            currIdx += 1;
-            unrealizedCount.current = 0;
+        const realized_unlisten = listen<PositionPnlRealized[]>("pos_pnl_realized", (event) => {
+            for (const realized of event.payload) {
+                setPnl(0.0);
+                positions.current.add(realized.position_id);
+                currIdx += 1;
+                unrealizedCount.current = 0;
 
-            if (lineSeries.current !== undefined) {
-                lineSeries.current.update({ time: currIdx as UTCTimestamp, value: 0 });
+                if (lineSeries.current !== undefined) {
+                    lineSeries.current.update({ time: currIdx as UTCTimestamp, value: 0 });
+                }
             }
         });
 
-        const unrealized_unlisten = listen<PositionPnlUnrealized>("pos_pnl_unrealized", (event) => {
-            if (lineSeries.current !== undefined) {
-                unrealizedCount.current += 1;
-                if (positions.current.has(event.payload.position_id) === false && unrealizedCount.current >= unrealizedCountThreshold) {
-                    currIdx += 1;
-                    lineSeries.current.update({ time: currIdx as UTCTimestamp, value: event.payload.value!.value });
-                    setPnl(Number(event.payload.value!.value.toFixed(2)));
+        const unrealized_unlisten = listen<PositionPnlUnrealized[]>("pos_pnl_unrealized", (event) => {
+            for (const unrealized of event.payload) {
+                if (lineSeries.current !== undefined) {
+                    unrealizedCount.current += 1;
+                    if (positions.current.has(unrealized.position_id) === false && unrealizedCount.current >= unrealizedCountThreshold) {
+                        currIdx += 1;
+                        lineSeries.current.update({ time: currIdx as UTCTimestamp, value: unrealized.value!.value });
+                        setPnl(Number(unrealized.value!.value.toFixed(2)));
+                    }
                 }
             }
         });
@@ -0,0 +1,46 @@
+import React, { useEffect } from "react";
+import { ActionIcon, Tooltip } from "@mantine/core";
+import { IconPlayerRecord, IconPlayerStop } from "@tabler/icons-react";
+import { listen } from "@tauri-apps/api/event";
+import { invoke } from "@tauri-apps/api/tauri";
+import { CommandError, RecordingStatus } from "./types";
+
+// Starts and stops recording the app feeds to a session file
+export function RecordButton() {
+    let [recording, setRecording] = React.useState<RecordingStatus | null>(null);
+
+    useEffect(() => {
+        invoke<RecordingStatus | null>("recording_status").then(setRecording);
+
+        const recording_unlisten = listen<RecordingStatus | null>("recording", (event) => {
+            setRecording(event.payload);
+        });
+
+        return () => {
+            recording_unlisten.then((fn) => fn()); // Ensure proper cleanup of the listener
+        };
+    }, []);
+
+    return (
+        <Tooltip label={recording !== null ? "Recording to " + recording.path : "Record app feeds"}>
+            <ActionIcon
+                variant="filled"
+                aria-label="Settings"
+                size="xl"
+                radius="0"
+                color="#242424"
+                onClick={(e: any) => {
+                    invoke(recording !== null ? "stop_recording" : "start_recording").catch((error: CommandError) =>
+                        console.error(error.message)
+                    );
+                }}
+            >
+                {recording !== null ? (
+                    <IconPlayerStop style={{ width: "70%", height: "70%" }} stroke={1.0} color="red" />
+                ) : (
+                    <IconPlayerRecord style={{ width: "70%", height: "70%" }} stroke={1.0} />
+                )}
+            </ActionIcon>
+        </Tooltip>
+    );
+}
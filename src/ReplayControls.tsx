@@ -0,0 +1,111 @@
+import React, { useEffect } from "react";
+import { ActionIcon, Menu, Slider, Text } from "@mantine/core";
+import { IconPlayerPause, IconPlayerPlay, IconMovie, IconX } from "@tabler/icons-react";
+import { listen } from "@tauri-apps/api/event";
+import { invoke } from "@tauri-apps/api/tauri";
+import { ReplayStatus } from "./types";
+import { logCommandError } from "./commandError";
+
+const speeds = [0.5, 1, 2, 5, 10, 50];
+
+// Opens a recorded session and controls its replay
+export function ReplayControls() {
+    let [replay, setReplay] = React.useState<ReplayStatus | null>(null);
+
+    useEffect(() => {
+        invoke<ReplayStatus | null>("replay_status").then(setReplay);
+
+        const replay_unlisten = listen<ReplayStatus>("replay", (event) => {
+            setReplay(event.payload);
+        });
+
+        const closed_unlisten = listen<ReplayStatus>("replay_closed", (_event) => {
+            setReplay(null);
+        });
+
+        return () => {
+            replay_unlisten.then((fn) => fn()); // Ensure proper cleanup of the listener
+            closed_unlisten.then((fn) => fn());
+        };
+    }, []);
+
+    return (
+        <>
+            <ActionIcon
+                variant="filled"
+                aria-label="Settings"
+                size="xl"
+                radius="0"
+                color="#242424"
+                onClick={(e: any) => {
+                    invoke("replay_open").catch(logCommandError);
+                }}
+            >
+                <IconMovie style={{ width: "70%", height: "70%" }} stroke={1.0} />
+            </ActionIcon>
+            {replay !== null && (
+                <>
+                    <ActionIcon
+                        variant="filled"
+                        aria-label="Settings"
+                        size="xl"
+                        radius="0"
+                        color="#242424"
+                        onClick={(e: any) => {
+                            invoke(replay.playing ? "replay_pause" : "replay_play").catch(logCommandError);
+                        }}
+                    >
+                        {replay.playing ? (
+                            <IconPlayerPause style={{ width: "70%", height: "70%" }} stroke={1.0} />
+                        ) : (
+                            <IconPlayerPlay style={{ width: "70%", height: "70%" }} stroke={1.0} />
+                        )}
+                    </ActionIcon>
+                    <Slider
+                        style={{ width: "200px", alignSelf: "center", margin: "0 10px" }}
+                        min={0}
+                        max={replay.duration_ns}
+                        value={replay.position_ns}
+                        label={(value) => (value / 1000000000).toFixed(1) + "s"}
+                        onChangeEnd={(value) => {
+                            invoke("replay_seek", { positionNs: value }).catch(logCommandError);
+                        }}
+                    />
+                    <Menu>
+                        <Menu.Target>
+                            <ActionIcon size="xl" variant="filled" color="#242424">
+                                <Text size="xs">{replay.speed}x</Text>
+                            </ActionIcon>
+                        </Menu.Target>
+
+                        <Menu.Dropdown>
+                            <Menu.Label>Speed</Menu.Label>
+                            {speeds.map((speed) => (
+                                <Menu.Item
+                                    key={speed}
+                                    onClick={(e: any) => {
+                                        invoke("replay_speed", { speed: speed }).catch(logCommandError);
+                                    }}
+                                >
+                                    {speed}x
+                                </Menu.Item>
+                            ))}
+                        </Menu.Dropdown>
+                    </Menu>
+                    <ActionIcon
+                        variant="filled"
+                        aria-label="Close replay"
+                        size="xl"
+                        radius="0"
+                        color="#242424"
+                        onClick={(e: any) => {
+                            invoke("replay_close").catch(logCommandError);
+                        }}
+                    >
+                        <IconX style={{ width: "70%", height: "70%" }} stroke={1.0} />
+                    </ActionIcon>
+                </>
+            )}
+        </>
+    );
+}
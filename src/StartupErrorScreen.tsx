@@ -0,0 +1,33 @@
+import { Alert, Code, List, Paper, Text, Title } from "@mantine/core";
+import { IconAlertTriangle } from "@tabler/icons-react";
+import { StartupError } from "./types";
+
+interface StartupErrorScreenProps {
+    startupError: StartupError;
+}
+
+export function StartupErrorScreen({ startupError }: StartupErrorScreenProps) {
+    return (
+        <div style={{ padding: "30px" }}>
+            <Paper withBorder p="xl">
+                <Title order={3}>Unable to connect to TradeBot services</Title>
+                <Text mt="sm">
+                    The service config <Code>{startupError.config}</Code> could not be used:
+                </Text>
+                <Alert mt="md" color="red" variant="light" icon={<IconAlertTriangle />}>
+                    <List spacing="xs">
+                        {startupError.errors.map((error, i) => (
+                            <List.Item key={i}>
+                                <Text c={error.fatal ? "red" : "yellow"}>{error.message}</Text>
+                            </List.Item>
+                        ))}
+                    </List>
+                </Alert>
+                <Text mt="md" c="dimmed">
+                    Fix the config, or point the app at another one with --config or the TRADEBOT_APP_CONFIG
+                    environment variable, then restart the app.
+                </Text>
+            </Paper>
+        </div>
+    );
+}
@@ -0,0 +1,65 @@
+import React, { useEffect, useMemo, useState } from "react";
+import { Flex, Paper, Title } from "@mantine/core";
+import { invoke } from "@tauri-apps/api/tauri";
+import { appWindow } from "@tauri-apps/api/window";
+import { ChartContainer } from "./Chart";
+import { TotalPnlChart } from "./TotalPnlChart";
+import { PositionPnlChart } from "./PositionPnlChart";
+import { OrderTable } from "./OrderTable";
+import { Stats } from "./Stats";
+import { WindowScope } from "./types";
+
+// Window showing a single strategy, opened with open_strategy_window. Only that strategy's streams
+// are routed here
+export function StrategyWindow() {
+    let [scope, setScope] = useState<WindowScope | null>(null);
+    // Set by open_strategy_window for strategies read from logs
+    const fromLog = new URLSearchParams(window.location.search).get("fromLog") === "true";
+
+    useEffect(() => {
+        invoke<WindowScope | null>("window_scope").then(setScope);
+    }, []);
+
+    useEffect(() => {
+        // Runs after the charts below set up their listeners, so the backend can request the chart now
+        if (scope !== null) {
+            appWindow.emit("strategy_window_ready");
+        }
+    }, [scope]);
+
+    // Kept stable across renders, the charts reset whenever the selected strategy changes
+    const selectedStrategy = useMemo(
+        () => ({ batchId: scope?.batchId ?? "", strategyId: scope?.strategyId ?? "" }),
+        [scope]
+    );
+
+    if (scope === null) {
+        return null;
+    }
+
+    return (
+        <div style={{ padding: "30px" }}>
+            <Title order={4}>
+                {scope.batchId} / {scope.strategyId}
+            </Title>
+            <Flex direction="row" height="size-6000" gap="lg">
+                <Paper withBorder>
+                    <ChartContainer
+                        selectedStrategy={selectedStrategy}
+                        activePeriod={scope.periodS}
+                        activeSymbol={scope.symbol}
+                        liveModeActive={!fromLog}
+                        refresh={false}
+                        detached={true}
+                    />
+                </Paper>
+                <Flex direction="column" gap="xl">
+                    <TotalPnlChart selectedStrategy={selectedStrategy} />
+                    <PositionPnlChart selectedStrategy={selectedStrategy} />
+                </Flex>
+            </Flex>
+            <Stats refresh={false} statsType="position_stats"></Stats>
+            <OrderTable selectedStrategy={selectedStrategy}></OrderTable>
+        </div>
+    );
+}
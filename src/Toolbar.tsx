@@ -6,10 +6,16 @@ import {
     IconRocket,
     IconHeartRateMonitor,
     IconReportAnalytics,
+    IconExternalLink,
 } from "@tabler/icons-react";
 import { invoke } from "@tauri-apps/api";
 import { SelectedStrategy, Batch } from "./types";
+import { ConnectionBadge } from "./ConnectionBadge";
+import { FeedMenu } from "./FeedMenu";
+import { RecordButton } from "./RecordButton";
+import { ReplayControls } from "./ReplayControls";
 import { Dispatch, SetStateAction } from "react"
+import { logCommandError } from "./commandError";
 
 interface ToolbarProps {
     batchesMap: Map<string, Batch>,
@@ -17,6 +23,7 @@ interface ToolbarProps {
     activeSymbol: string | undefined,
     activePeriod: number | undefined,
     loading: boolean,
+    liveModeActive: boolean,
     setSelectedStrategy: Dispatch<SetStateAction<SelectedStrategy>>,
     setActiveSymbol: Dispatch<SetStateAction<string | undefined>>,
     setActivePeriod: Dispatch<SetStateAction<number | undefined>>,
@@ -34,6 +41,7 @@ export function Toolbar({
     setActivePeriod,
     setActiveSymbol,
     loading,
+    liveModeActive,
     setRefresh,
     setView,
     setSelectedOverallBatch,
@@ -100,11 +108,11 @@ export function Toolbar({
                 color="#242424"
                 onClick={(e: any) => {
                     setRefresh(true);
-                    invoke("chart_request", { batchId: "", strategyId: "", symbol: "", periodS: 0 });
+                    invoke("chart_request", { batchId: "", strategyId: "", symbol: "", periodS: 0 }).catch(logCommandError);
                     setSelectedStrategy({ batchId: "", strategyId: "" });
                     setActiveSymbol("");
                     setActivePeriod(0);
-                    invoke("app_request");
+                    invoke("app_request").catch(logCommandError);
                     setView("strategy");
                 }}
             >
@@ -117,7 +125,7 @@ export function Toolbar({
                 radius="0"
                 color="#242424"
                 onClick={(e: any) => {
-                    invoke("read_from_dir");
+                    invoke("read_from_dir").catch(logCommandError);
                 }}
             >
                 <IconFolderOpen style={{ width: "70%", height: "70%" }} stroke={1.0} />
@@ -129,11 +137,31 @@ export function Toolbar({
                 radius="0"
                 color="#242424"
                 onClick={(e: any) => {
-                    invoke("run_yaml");
+                    invoke("run_yaml").catch(logCommandError);
                 }}
             >
                 <IconRocket style={{ width: "70%", height: "70%" }} stroke={1.0} />
             </ActionIcon>
+            <ActionIcon
+                variant="filled"
+                aria-label="Settings"
+                size="xl"
+                radius="0"
+                color="#242424"
+                disabled={selectedStrategy.strategyId.length == 0 || !activeSymbol || !activePeriod}
+                onClick={(e: any) => {
+                    // Detach the selected strategy chart into its own window
+                    invoke("open_strategy_window", {
+                        batchId: selectedStrategy.batchId,
+                        strategyId: selectedStrategy.strategyId,
+                        symbol: activeSymbol,
+                        periodS: activePeriod,
+                        fromLog: !liveModeActive,
+                    }).catch(logCommandError);
+                }}
+            >
+                <IconExternalLink style={{ width: "70%", height: "70%" }} stroke={1.0} />
+            </ActionIcon>
             <Menu>
                 <Menu.Target>
                     <ActionIcon
@@ -204,6 +232,9 @@ export function Toolbar({
                         ))}
                 </Menu.Dropdown>
             </Menu>
+            <FeedMenu />
+            <RecordButton />
+            <ReplayControls />
             <Divider size="md" orientation="vertical" />
             <>
                 {symbolList.length == 1 && (
@@ -262,6 +293,7 @@ export function Toolbar({
                     </Menu.Dropdown>
                 </Menu>
             </>
+            <ConnectionBadge />
         </Flex>
     );
 }
@@ -39,22 +39,26 @@ export function TotalPnlChart({ selectedStrategy }: TotalPnlChartProps) {
             }
         });
 
-        const realized_unlisten = listen<TotalPnlRealized>("total_pnl_realized", (event) => {
-            if (chart.current !== undefined && lineSeries.current !== undefined) {
-                lineSeries.current.update({ time: pointIdx as UTCTimestamp, value: event.payload.value!.value });
-                pointIdx += 1;
-                setPnl(Number(event.payload.value!.value.toFixed(2)));
+        const realized_unlisten = listen<TotalPnlRealized[]>("total_pnl_realized", (event) => {
+            for (const realized of event.payload) {
+                if (chart.current !== undefined && lineSeries.current !== undefined) {
+                    lineSeries.current.update({ time: pointIdx as UTCTimestamp, value: realized.value!.value });
+                    pointIdx += 1;
+                    setPnl(Number(realized.value!.value.toFixed(2)));
 
-                closedPositions.current.add(event.payload.position_id);
-                chart.current.timeScale().fitContent();
+                    closedPositions.current.add(realized.position_id);
+                    chart.current.timeScale().fitContent();
+                }
             }
         });
 
-        const unrealized_unlisten = listen<TotalPnlUnrealized>("total_pnl_unrealized", (event) => {
-            if (chart.current !== undefined && lineSeries.current !== undefined) {
-                if (!closedPositions.current.has(event.payload.position_id)) {
-                    lineSeries.current.update({ time: pointIdx as UTCTimestamp, value: event.payload.value!.value });
-                    setPnl(Number(event.payload.value!.value.toFixed(2)));
+        const unrealized_unlisten = listen<TotalPnlUnrealized[]>("total_pnl_unrealized", (event) => {
+            for (const unrealized of event.payload) {
+                if (chart.current !== undefined && lineSeries.current !== undefined) {
+                    if (!closedPositions.current.has(unrealized.position_id)) {
+                        lineSeries.current.update({ time: pointIdx as UTCTimestamp, value: unrealized.value!.value });
+                        setPnl(Number(unrealized.value!.value.toFixed(2)));
+                    }
                 }
             }
         });
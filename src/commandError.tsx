@@ -0,0 +1,9 @@
+import { CommandError } from "./types";
+
+// Log a rejected invoke. Closing a file dialog without picking anything is not an error worth
+// reporting
+export function logCommandError(error: CommandError) {
+    if (error.kind !== "dialog_cancelled") {
+        console.error(error.message);
+    }
+}
@@ -59,6 +59,86 @@ interface OrderTableEntry {
     realized: string;
 }
 
+interface ConfigError {
+    kind: string;
+    message: string;
+    fatal: boolean;
+}
+
+interface StartupError {
+    config: string;
+    errors: Array<ConfigError>;
+}
+
+interface ConnectionStatus {
+    state: "connecting" | "connected" | "degraded" | "disconnected";
+    attempt: number;
+    error: string | null;
+    retry_in_ms: number | null;
+}
+
+interface ServiceHealth {
+    service: "app" | "datafeed" | "algo" | "broker";
+    alive: boolean;
+    last_seen_ms: number | null;
+    latency_ms: number | null;
+}
+
+interface AdvertisedTopic {
+    feed_id: string;
+    topic: string;
+    mtype_id: number;
+    mtype: string | null;
+    status: "subscribed" | "unsupported" | "unknown_type" | "failed";
+    error: string | null;
+}
+
+interface FeedInfo {
+    id: string;
+    batch_ids: string[];
+    topics: number;
+    started_ns: number;
+}
+
+interface FeedClosed {
+    id: string;
+    reason: "replaced" | "stopped";
+    finished: boolean;
+}
+
+interface FeedError {
+    kind: "missing_network" | "invalid_ip" | "unusable_ip" | "invalid_port" | "connect";
+    message: string;
+}
+
+interface RecordingStatus {
+    path: string;
+    records: number;
+    started_ns: number;
+}
+
+interface ReplayStatus {
+    path: string;
+    records: number;
+    position: number;
+    position_ns: number;
+    duration_ns: number;
+    playing: boolean;
+    speed: number;
+}
+
+interface WindowScope {
+    batchId: string;
+    strategyId: string;
+    symbol: string;
+    periodS: number;
+}
+
+interface CommandError {
+    kind: "publish" | "invalid_argument" | "dialog_cancelled" | "not_connected" | "timeout" | "open_window" | "unknown_feed" | "recording" | "replay" | "no_replay";
+    message: string;
+}
+
 export type { SelectedStrategy };
 export type { Batch };
 export type { Strategy };
@@ -67,3 +147,15 @@ export type { Legend };
 export type { LineVal };
 export type { StatsType };
 export type { OrderTableEntry };
+export type { ConfigError };
+export type { StartupError };
+export type { ConnectionStatus };
+export type { ServiceHealth };
+export type { CommandError };
+export type { AdvertisedTopic };
+export type { WindowScope };
+export type { FeedInfo };
+export type { FeedClosed };
+export type { FeedError };
+export type { RecordingStatus };
+export type { ReplayStatus };